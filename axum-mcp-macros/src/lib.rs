@@ -11,60 +11,35 @@ use syn::{FnArg, ItemFn, ReturnType, Type, parse_macro_input};
 pub fn mcp_tool(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input_fn = parse_macro_input!(item as ItemFn);
 
-    // Parse attributes: name = "...", desc = "...", state = "TypePath"
-    let parser = Punctuated::<Meta, Token![,]>::parse_terminated;
-    let metas = parser.parse(attr.into()).unwrap_or_default();
-    let mut tool_name: Option<String> = None;
-    let mut tool_desc: Option<String> = None;
-    let mut state_ty: Option<Path> = None;
-    let mut structured: Option<bool> = None;
-    for m in metas {
-        match m {
-            Meta::NameValue(nv) if nv.path.is_ident("name") => {
-                if let Expr::Lit(ExprLit {
-                    lit: Lit::Str(s), ..
-                }) = nv.value
-                {
-                    tool_name = Some(s.value());
-                }
-            }
-            Meta::NameValue(nv) if nv.path.is_ident("desc") => {
-                if let Expr::Lit(ExprLit {
-                    lit: Lit::Str(s), ..
-                }) = nv.value
-                {
-                    tool_desc = Some(s.value());
-                }
-            }
-            Meta::NameValue(nv) if nv.path.is_ident("state") => {
-                if let Expr::Lit(ExprLit {
-                    lit: Lit::Str(s), ..
-                }) = nv.value
-                {
-                    state_ty = Some(syn::parse_str(&s.value()).expect("invalid state type path"));
-                }
-            }
-            Meta::NameValue(nv) if nv.path.is_ident("structured") => {
-                if let Expr::Lit(ExprLit {
-                    lit: Lit::Bool(b), ..
-                }) = nv.value
-                {
-                    structured = Some(b.value);
-                }
-            }
-            _ => {}
-        }
-    }
+    let attrs = match parse_tool_attrs(attr.into()) {
+        Ok(a) => a,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let ToolAttrs { name: tool_name, desc: tool_desc, title, state: state_ty, structured, .. } = attrs;
 
     let fn_name = &input_fn.sig.ident;
     let vis = &input_fn.vis;
 
-    // Parse params: exactly one Json<T>, and a State<S> param
+    // Parse params: exactly one Json<T>, a State<S> param, and optionally &ToolCtx
     let mut json_ty: Option<Type> = None;
     let mut state_param_ty: Option<Type> = None;
+    let mut call_args = Vec::new();
     for p in &input_fn.sig.inputs {
         if let FnArg::Typed(pt) = p {
+            if let Type::Reference(r) = &*pt.ty {
+                if let Type::Path(tp) = &*r.elem {
+                    if tp.path.segments.last().is_some_and(|s| s.ident == "ToolCtx") {
+                        call_args.push(quote! { ctx });
+                    }
+                }
+            }
             if let Type::Path(tp) = &*pt.ty {
+                let last = &tp.path.segments.last().unwrap().ident;
+                if last == "Json" {
+                    call_args.push(quote! { axum::Json(input) });
+                } else if last == "State" {
+                    call_args.push(quote! { State(state_val) });
+                }
                 let segs = &tp.path.segments;
                 if segs.iter().any(|s| s.ident == "Json") {
                     if let syn::PathArguments::AngleBracketed(ab) = &segs.last().unwrap().arguments
@@ -106,39 +81,32 @@ pub fn mcp_tool(attr: TokenStream, item: TokenStream) -> TokenStream {
     let state_param_ty =
         state_param_ty.expect("#[mcp_tool] requires a State<S> parameter to downcast app_state");
 
-    // Parse return type: Json<O> or O
-    let (output_ty, _wrap_is_json) = match &input_fn.sig.output {
-        ReturnType::Type(_, ty) => {
-            if let Type::Path(tp) = &**ty {
-                let segs = &tp.path.segments;
-                if segs.iter().any(|s| s.ident == "Json") {
-                    if let syn::PathArguments::AngleBracketed(ab) = &segs.last().unwrap().arguments
-                    {
-                        if let Some(syn::GenericArgument::Type(t)) = ab.args.first() {
-                            (t.clone(), true)
-                        } else {
-                            panic!("unable to infer Json<Out> type")
-                        }
-                    } else {
-                        panic!("expected Json<Out>")
-                    }
-                } else {
-                    ((**ty).clone(), false)
-                }
-            } else {
-                panic!("return type must be concrete (Json<Out> or Out)")
-            }
-        }
-        _ => panic!("return type must be specified (Json<Out> or Out)"),
+    // The output schema comes from Json<O>, optionally inside a Result; other
+    // return types (text, content blocks, CallToolResult) have no fixed shape.
+    let json_out = match &input_fn.sig.output {
+        ReturnType::Type(_, ty) => json_output(ty),
+        ReturnType::Default => None,
     };
+    // Tools opt in to structured output with `structured`.
+    let structured_flag = structured.unwrap_or(false);
+    let output_ty = json_out.unwrap_or_else(|| syn::parse_quote!(serde_json::Value));
 
     let tool_name_lit = tool_name.unwrap_or_else(|| fn_name.to_string());
-    let structured_flag = structured.unwrap_or(false);
     let tool_desc_tokens = if let Some(d) = tool_desc {
         quote! { Some(#d) }
     } else {
         quote! { None }
     };
+    let title_tokens = match &title {
+        Some(t) => quote! { Some(#t) },
+        None => quote! { None },
+    };
+    let hint = |h: Option<bool>| match h {
+        Some(b) => quote! { Some(#b) },
+        None => quote! { None },
+    };
+    let (read_only, destructive) = (hint(attrs.read_only), hint(attrs.destructive));
+    let (idempotent, open_world) = (hint(attrs.idempotent), hint(attrs.open_world));
     let handler_ident = format_ident!("{}__mcp_tool_handler", fn_name.to_string());
     let reg_ident = format_ident!("{}_MCP_TOOL", fn_name.to_string().to_uppercase());
     // Link-time duplicate detection symbol removed to avoid unsafe attributes in expansion.
@@ -149,15 +117,15 @@ pub fn mcp_tool(attr: TokenStream, item: TokenStream) -> TokenStream {
         struct #handler_ident;
         #[async_trait::async_trait]
         impl axum_mcp::tool::ToolHandler for #handler_ident {
-            async fn call(&self, ctx: &axum_mcp::tool::ToolCtx, args: serde_json::Value) -> Result<serde_json::Value, axum_mcp::tool::ToolError> {
+            async fn call(&self, ctx: &axum_mcp::tool::ToolCtx, args: serde_json::Value) -> Result<axum_mcp::tool::CallToolResult, axum_mcp::tool::ToolError> {
                 use axum::extract::State;
                 let sref = ctx.app_state.as_ref().downcast_ref::<#state_param_ty>()
                     .ok_or_else(|| axum_mcp::tool::ToolError::Internal("invalid state type".into()))?;
                 let state_val: #state_param_ty = sref.clone();
                 let input: #json_ty = serde_json::from_value(args)
                     .map_err(|e| axum_mcp::tool::ToolError::InvalidArgs(e.to_string()))?;
-                let out = #fn_name(State(state_val), axum::Json(input)).await;
-                Ok(axum_mcp::IntoJsonValue::into_json_value(out))
+                let out = #fn_name(#(#call_args),*).await;
+                axum_mcp::tool::IntoToolResult::into_tool_result(out)
             }
         }
 
@@ -171,6 +139,13 @@ pub fn mcp_tool(attr: TokenStream, item: TokenStream) -> TokenStream {
             defined_at_file: file!(),
             defined_at_line: line!(),
             structured: #structured_flag,
+            title: #title_tokens,
+            annotations: axum_mcp::tool::ToolAnnotations {
+                read_only_hint: #read_only,
+                destructive_hint: #destructive,
+                idempotent_hint: #idempotent,
+                open_world_hint: #open_world,
+            },
         };
 
         // Duplicate detection is handled at registry-gather time with clear diagnostics.
@@ -178,3 +153,432 @@ pub fn mcp_tool(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     TokenStream::from(expanded)
 }
+
+#[derive(Default)]
+struct ToolAttrs {
+    name: Option<String>,
+    desc: Option<String>,
+    title: Option<String>,
+    state: Option<Type>,
+    structured: Option<bool>,
+    read_only: Option<bool>,
+    destructive: Option<bool>,
+    idempotent: Option<bool>,
+    open_world: Option<bool>,
+}
+
+fn parse_tool_attrs(attr: proc_macro2::TokenStream) -> syn::Result<ToolAttrs> {
+    let metas = Punctuated::<Meta, Token![,]>::parse_terminated.parse2(attr)?;
+    let mut attrs = ToolAttrs::default();
+    for m in metas {
+        // Flags may be bare: `read_only` means `read_only = true`.
+        let (path, value) = match m {
+            Meta::Path(path) => (path, None),
+            Meta::NameValue(nv) => (nv.path, Some(nv.value)),
+            other => return Err(syn::Error::new_spanned(other, "expected `key = value` or a flag")),
+        };
+        let flag = match path.get_ident().map(|i| i.to_string()).as_deref() {
+            Some("name") => {
+                attrs.name = Some(str_value(&path, value)?);
+                continue;
+            }
+            Some("desc") => {
+                attrs.desc = Some(str_value(&path, value)?);
+                continue;
+            }
+            Some("title") => {
+                attrs.title = Some(str_value(&path, value)?);
+                continue;
+            }
+            Some("state") => {
+                attrs.state = Some(syn::parse_str(&str_value(&path, value)?)?);
+                continue;
+            }
+            Some("structured") => &mut attrs.structured,
+            Some("read_only") => &mut attrs.read_only,
+            Some("destructive") => &mut attrs.destructive,
+            Some("idempotent") => &mut attrs.idempotent,
+            Some("open_world") => &mut attrs.open_world,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    path,
+                    "unknown #[mcp_tool] attribute; expected name, desc, title, state, structured, \
+                     read_only, destructive, idempotent or open_world",
+                ));
+            }
+        };
+        *flag = Some(match value {
+            None => true,
+            Some(Expr::Lit(ExprLit { lit: Lit::Bool(b), .. })) => b.value,
+            Some(other) => return Err(syn::Error::new_spanned(other, "expected `true` or `false`")),
+        });
+    }
+    Ok(attrs)
+}
+
+fn str_value(path: &Path, value: Option<Expr>) -> syn::Result<String> {
+    match value {
+        Some(Expr::Lit(ExprLit { lit: Lit::Str(s), .. })) => Ok(s.value()),
+        Some(other) => Err(syn::Error::new_spanned(other, "expected a string literal")),
+        None => Err(syn::Error::new_spanned(path, "expected `key = \"value\"`")),
+    }
+}
+
+fn lit_str(nv: &syn::MetaNameValue) -> syn::Result<String> {
+    match &nv.value {
+        Expr::Lit(ExprLit { lit: Lit::Str(s), .. }) => Ok(s.value()),
+        other => Err(syn::Error::new_spanned(other, "expected a string literal")),
+    }
+}
+
+/// `complete(arg = provider_fn, ...)`: the fns are passed to `completion::from_fn`.
+fn parse_complete(list: &syn::MetaList) -> syn::Result<Vec<(String, Expr)>> {
+    let pairs = list.parse_args_with(Punctuated::<syn::MetaNameValue, Token![,]>::parse_terminated)?;
+    pairs
+        .into_iter()
+        .map(|nv| {
+            let arg = nv.path.get_ident().ok_or_else(|| {
+                syn::Error::new_spanned(&nv.path, "expected an argument name")
+            })?;
+            Ok((arg.to_string(), nv.value))
+        })
+        .collect()
+}
+
+fn completions_tokens(completions: &[(String, Expr)]) -> proc_macro2::TokenStream {
+    let (args, fns): (Vec<_>, Vec<_>) = completions.iter().cloned().unzip();
+    quote! { || vec![#((#args, axum_mcp::completion::from_fn(#fns))),*] }
+}
+
+fn json_output(ty: &Type) -> Option<Type> {
+    let Type::Path(tp) = ty else { return None };
+    match tp.path.segments.last()?.ident.to_string().as_str() {
+        "Json" => first_generic(tp),
+        "Result" => json_output(&first_generic(tp)?),
+        _ => None,
+    }
+}
+
+fn first_generic(tp: &syn::TypePath) -> Option<Type> {
+    if let syn::PathArguments::AngleBracketed(ab) = &tp.path.segments.last()?.arguments {
+        if let Some(syn::GenericArgument::Type(t)) = ab.args.first() {
+            return Some(t.clone());
+        }
+    }
+    None
+}
+
+/// Register an axum handler as an MCP resource.
+///
+/// `uri` may be a URI template whose variables fill the handler's `Path<T>`,
+/// so `GET /files/{id}` can also be read as `file:///files/{id}`.
+#[proc_macro_attribute]
+pub fn mcp_resource(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input_fn = parse_macro_input!(item as ItemFn);
+    match expand_resource(attr.into(), input_fn) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand_resource(
+    attr: proc_macro2::TokenStream,
+    input_fn: ItemFn,
+) -> syn::Result<proc_macro2::TokenStream> {
+    // Parse attributes: uri = "...", name = "...", desc = "...", mime = "...", state = "TypePath",
+    // complete(var = provider_fn, ...)
+    let metas = Punctuated::<Meta, Token![,]>::parse_terminated.parse2(attr)?;
+    let mut uri: Option<String> = None;
+    let mut name: Option<String> = None;
+    let mut desc: Option<String> = None;
+    let mut mime: Option<String> = None;
+    let mut state_ty: Option<Type> = None;
+    let mut completions = Vec::new();
+    for m in metas {
+        let nv = match m {
+            Meta::NameValue(nv) => nv,
+            Meta::List(list) if list.path.is_ident("complete") => {
+                completions.extend(parse_complete(&list)?);
+                continue;
+            }
+            other => return Err(syn::Error::new_spanned(other, "expected `key = \"value\"`")),
+        };
+        let value = lit_str(&nv)?;
+        match nv.path.get_ident().map(|i| i.to_string()).as_deref() {
+            Some("uri") => uri = Some(value),
+            Some("name") => name = Some(value),
+            Some("desc") => desc = Some(value),
+            Some("mime") => mime = Some(value),
+            Some("state") => state_ty = Some(syn::parse_str(&value)?),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    nv.path,
+                    "unknown #[mcp_resource] attribute; expected uri, name, desc, mime, state or complete(..)",
+                ));
+            }
+        }
+    }
+    let fn_name = &input_fn.sig.ident;
+    let uri = uri.ok_or_else(|| {
+        syn::Error::new_spanned(fn_name, "#[mcp_resource] requires `uri = \"...\"`")
+    })?;
+
+    // Params, all optional: State<S>, Path<T> (from the template variables), &ToolCtx
+    let mut state_param_ty: Option<Type> = None;
+    let mut path_ty: Option<Type> = None;
+    let mut call_args = Vec::new();
+    for p in &input_fn.sig.inputs {
+        let FnArg::Typed(pt) = p else {
+            return Err(syn::Error::new_spanned(p, "#[mcp_resource] handlers cannot take self"));
+        };
+        match &*pt.ty {
+            Type::Reference(r)
+                if matches!(&*r.elem, Type::Path(tp) if tp.path.segments.last().is_some_and(|s| s.ident == "ToolCtx")) =>
+            {
+                call_args.push(quote! { ctx });
+            }
+            Type::Path(tp) if tp.path.segments.last().is_some_and(|s| s.ident == "State") => {
+                state_param_ty = first_generic(tp);
+                call_args.push(quote! { State(state_val) });
+            }
+            Type::Path(tp) if tp.path.segments.last().is_some_and(|s| s.ident == "Path") => {
+                path_ty = first_generic(tp);
+                call_args.push(quote! { Path(path_val) });
+            }
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "#[mcp_resource] handlers take only State<S>, Path<T> and &ToolCtx",
+                ));
+            }
+        }
+    }
+    if let (Some(attr_state), Some(sig_state)) = (state_ty.as_ref(), state_param_ty.as_ref()) {
+        let (a, b) = (quote!(#attr_state).to_string(), quote!(#sig_state).to_string());
+        if a != b {
+            return Err(syn::Error::new_spanned(
+                fn_name,
+                format!("#[mcp_resource(state = \"{a}\")] does not match State<{b}> parameter"),
+            ));
+        }
+    }
+    let templated = uri.contains('{');
+    if templated != path_ty.is_some() {
+        return Err(syn::Error::new_spanned(
+            fn_name,
+            if templated {
+                "URI template variables need a Path<T> parameter"
+            } else {
+                "Path<T> needs a URI template such as \"file:///files/{id}\""
+            },
+        ));
+    }
+
+    let state_extract = state_param_ty.map(|s| {
+        quote! {
+            let sref = ctx.app_state.as_ref().downcast_ref::<#s>()
+                .ok_or_else(|| axum_mcp::resource::ResourceError::Internal("invalid state type".into()))?;
+            let state_val: #s = sref.clone();
+        }
+    });
+    let path_extract = path_ty.map(|t| {
+        quote! { let path_val: #t = axum_mcp::resource::path_from_vars(uri, vars)?; }
+    });
+    let opt = |v: Option<String>| match v {
+        Some(v) => quote! { Some(#v) },
+        None => quote! { None },
+    };
+    let name = name.unwrap_or_else(|| fn_name.to_string());
+    let desc_tokens = opt(desc);
+    let mime_tokens = opt(mime);
+    let completions_tokens = completions_tokens(&completions);
+    let vis = &input_fn.vis;
+    let handler_ident = format_ident!("{}__mcp_resource_handler", fn_name.to_string());
+    let reg_ident = format_ident!("{}_MCP_RESOURCE", fn_name.to_string().to_uppercase());
+
+    Ok(quote! {
+        #vis #input_fn
+
+        struct #handler_ident;
+        #[async_trait::async_trait]
+        impl axum_mcp::resource::ResourceHandler for #handler_ident {
+            #[allow(unused_variables)]
+            async fn read(&self, ctx: &axum_mcp::tool::ToolCtx, uri: &str, vars: &axum_mcp::resource::UriVars) -> Result<Vec<axum_mcp::resource::ResourceContents>, axum_mcp::resource::ResourceError> {
+                use axum::extract::{Path, State};
+                #state_extract
+                #path_extract
+                let out = #fn_name(#(#call_args),*).await;
+                axum_mcp::resource::IntoResourceContents::into_resource_contents(out, uri, #mime_tokens)
+            }
+        }
+
+        #[linkme::distributed_slice(axum_mcp::resource::RESOURCES)]
+        pub static #reg_ident: axum_mcp::resource::ResourceRegistration = axum_mcp::resource::ResourceRegistration {
+            uri: #uri,
+            name: #name,
+            description: #desc_tokens,
+            mime_type: #mime_tokens,
+            build_handler: || std::sync::Arc::new(#handler_ident),
+            completions: #completions_tokens,
+            defined_at_file: file!(),
+            defined_at_line: line!(),
+        };
+    })
+}
+
+/// Register an async fn as an MCP prompt.
+///
+/// The fn may take its arguments as a `schemars` struct (plain or in
+/// `Json<T>`), a `State<S>` and a `&ToolCtx`, and returns prompt messages.
+#[proc_macro_attribute]
+pub fn mcp_prompt(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input_fn = parse_macro_input!(item as ItemFn);
+    match expand_prompt(attr.into(), input_fn) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand_prompt(
+    attr: proc_macro2::TokenStream,
+    input_fn: ItemFn,
+) -> syn::Result<proc_macro2::TokenStream> {
+    // Parse attributes: name = "...", desc = "...", state = "TypePath", complete(arg = provider_fn, ...)
+    let metas = Punctuated::<Meta, Token![,]>::parse_terminated.parse2(attr)?;
+    let mut name: Option<String> = None;
+    let mut desc: Option<String> = None;
+    let mut state_ty: Option<Type> = None;
+    let mut completions = Vec::new();
+    for m in metas {
+        let nv = match m {
+            Meta::NameValue(nv) => nv,
+            Meta::List(list) if list.path.is_ident("complete") => {
+                completions.extend(parse_complete(&list)?);
+                continue;
+            }
+            other => return Err(syn::Error::new_spanned(other, "expected `key = \"value\"`")),
+        };
+        let value = lit_str(&nv)?;
+        match nv.path.get_ident().map(|i| i.to_string()).as_deref() {
+            Some("name") => name = Some(value),
+            Some("desc") => desc = Some(value),
+            Some("state") => state_ty = Some(syn::parse_str(&value)?),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    nv.path,
+                    "unknown #[mcp_prompt] attribute; expected name, desc, state or complete(..)",
+                ));
+            }
+        }
+    }
+    let fn_name = &input_fn.sig.ident;
+
+    // Params, all optional: State<S>, &ToolCtx and one arguments struct (T or Json<T>)
+    let mut state_param_ty: Option<Type> = None;
+    let mut args_ty: Option<Type> = None;
+    let mut call_args = Vec::new();
+    for p in &input_fn.sig.inputs {
+        let FnArg::Typed(pt) = p else {
+            return Err(syn::Error::new_spanned(p, "#[mcp_prompt] functions cannot take self"));
+        };
+        match &*pt.ty {
+            Type::Reference(r)
+                if matches!(&*r.elem, Type::Path(tp) if tp.path.segments.last().is_some_and(|s| s.ident == "ToolCtx")) =>
+            {
+                call_args.push(quote! { ctx });
+            }
+            Type::Path(tp) if tp.path.segments.last().is_some_and(|s| s.ident == "State") => {
+                state_param_ty = first_generic(tp);
+                call_args.push(quote! { State(state_val) });
+            }
+            ty if args_ty.is_none() => {
+                let json_inner = match ty {
+                    Type::Path(tp) if tp.path.segments.last().is_some_and(|s| s.ident == "Json") => {
+                        first_generic(tp)
+                    }
+                    _ => None,
+                };
+                match json_inner {
+                    Some(inner) => {
+                        args_ty = Some(inner);
+                        call_args.push(quote! { axum::Json(input) });
+                    }
+                    None => {
+                        args_ty = Some(ty.clone());
+                        call_args.push(quote! { input });
+                    }
+                }
+            }
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "#[mcp_prompt] functions take one arguments struct, State<S> and &ToolCtx",
+                ));
+            }
+        }
+    }
+    if let (Some(attr_state), Some(sig_state)) = (state_ty.as_ref(), state_param_ty.as_ref()) {
+        let (a, b) = (quote!(#attr_state).to_string(), quote!(#sig_state).to_string());
+        if a != b {
+            return Err(syn::Error::new_spanned(
+                fn_name,
+                format!("#[mcp_prompt(state = \"{a}\")] does not match State<{b}> parameter"),
+            ));
+        }
+    }
+
+    let state_extract = state_param_ty.map(|s| {
+        quote! {
+            let sref = ctx.app_state.as_ref().downcast_ref::<#s>()
+                .ok_or_else(|| axum_mcp::prompt::PromptError::Internal("invalid state type".into()))?;
+            let state_val: #s = sref.clone();
+        }
+    });
+    let args_extract = args_ty.as_ref().map(|t| {
+        quote! {
+            let input: #t = serde_json::from_value(args)
+                .map_err(|e| axum_mcp::prompt::PromptError::InvalidArgs(e.to_string()))?;
+        }
+    });
+    let schema_tokens = match &args_ty {
+        Some(t) => quote! { Some(|| schemars::schema_for!(#t)) },
+        None => quote! { None },
+    };
+    let name = name.unwrap_or_else(|| fn_name.to_string());
+    let desc_tokens = match desc {
+        Some(d) => quote! { Some(#d) },
+        None => quote! { None },
+    };
+    let completions_tokens = completions_tokens(&completions);
+    let vis = &input_fn.vis;
+    let handler_ident = format_ident!("{}__mcp_prompt_handler", fn_name.to_string());
+    let reg_ident = format_ident!("{}_MCP_PROMPT", fn_name.to_string().to_uppercase());
+
+    Ok(quote! {
+        #vis #input_fn
+
+        struct #handler_ident;
+        #[async_trait::async_trait]
+        impl axum_mcp::prompt::PromptHandler for #handler_ident {
+            #[allow(unused_variables)]
+            async fn get(&self, ctx: &axum_mcp::tool::ToolCtx, args: serde_json::Value) -> Result<Vec<axum_mcp::prompt::PromptMessage>, axum_mcp::prompt::PromptError> {
+                use axum::extract::State;
+                #state_extract
+                #args_extract
+                let out = #fn_name(#(#call_args),*).await;
+                axum_mcp::prompt::IntoPromptMessages::into_prompt_messages(out)
+            }
+        }
+
+        #[linkme::distributed_slice(axum_mcp::prompt::PROMPTS)]
+        pub static #reg_ident: axum_mcp::prompt::PromptRegistration = axum_mcp::prompt::PromptRegistration {
+            name: #name,
+            description: #desc_tokens,
+            arguments_schema: #schema_tokens,
+            build_handler: || std::sync::Arc::new(#handler_ident),
+            completions: #completions_tokens,
+            defined_at_file: file!(),
+            defined_at_line: line!(),
+        };
+    })
+}
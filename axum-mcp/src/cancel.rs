@@ -0,0 +1,138 @@
+//! In-flight request tracking for `notifications/cancelled`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+pub use tokio_util::sync::CancellationToken;
+
+use crate::jsonrpc::RequestId;
+
+pub const CANCELLED_NOTIFICATION: &str = "notifications/cancelled";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CancelledParams {
+    pub request_id: RequestId,
+}
+
+/// Cancellation tokens of the requests a client currently has running, by id.
+#[derive(Default)]
+pub struct InFlight {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    next_seq: u64,
+    requests: HashMap<RequestId, (u64, CancellationToken)>,
+}
+
+impl InFlight {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a request. Dropping the guard forgets it and cancels its
+    /// token, so a request abandoned mid-way (e.g. its connection dropped)
+    /// still tells anything the tool spawned to stop.
+    pub fn start(self: &Arc<Self>, id: RequestId) -> InFlightGuard {
+        let token = CancellationToken::new();
+        let mut state = self.state.lock().unwrap();
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        state.requests.insert(id.clone(), (seq, token.clone()));
+        InFlightGuard { in_flight: self.clone(), id, seq, token }
+    }
+
+    /// Cancel the request with this id; false when none is running.
+    pub fn cancel(&self, id: &RequestId) -> bool {
+        match self.state.lock().unwrap().requests.get(id) {
+            Some((_, token)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn cancel_all(&self) {
+        for (_, token) in self.state.lock().unwrap().requests.values() {
+            token.cancel();
+        }
+    }
+
+    /// Apply a `notifications/cancelled`; malformed or stale ones are ignored,
+    /// as the spec asks.
+    pub fn on_cancelled(&self, params: Option<serde_json::Value>) {
+        if let Some(params) = params
+            && let Ok(CancelledParams { request_id }) = serde_json::from_value(params)
+        {
+            self.cancel(&request_id);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+pub struct InFlightGuard {
+    in_flight: Arc<InFlight>,
+    id: RequestId,
+    seq: u64,
+    token: CancellationToken,
+}
+
+impl InFlightGuard {
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.token.cancel();
+        let mut state = self.in_flight.state.lock().unwrap();
+        // A newer request may have reused the id; leave its entry alone.
+        if state.requests.get(&self.id).is_some_and(|(seq, _)| *seq == self.seq) {
+            state.requests.remove(&self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn cancel_by_id_and_forget_on_drop() {
+        let in_flight = Arc::new(InFlight::new());
+        let guard = in_flight.start(RequestId::Number(1));
+        assert!(!guard.token().is_cancelled());
+        in_flight.on_cancelled(Some(json!({"requestId": 1, "reason": "user"})));
+        assert!(guard.token().is_cancelled());
+
+        let token = guard.token().clone();
+        drop(guard);
+        assert!(in_flight.is_empty());
+        assert!(!in_flight.cancel(&RequestId::Number(1)));
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn reused_id_keeps_the_newer_entry() {
+        let in_flight = Arc::new(InFlight::new());
+        let old = in_flight.start(RequestId::String("a".into()));
+        let new = in_flight.start(RequestId::String("a".into()));
+        drop(old);
+        assert_eq!(in_flight.len(), 1);
+        assert!(in_flight.cancel(&RequestId::String("a".into())));
+        assert!(new.token().is_cancelled());
+    }
+}
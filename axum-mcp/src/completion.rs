@@ -0,0 +1,273 @@
+//! Argument autocompletion (`completion/complete`) for prompt arguments and
+//! resource-template variables.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::jsonrpc::{self, ErrorObject};
+use crate::prompt::PromptRegistry;
+use crate::resource::ResourceRegistry;
+use crate::schema::RootSchema;
+use crate::tool::ToolCtx;
+
+/// The most values one response may carry.
+pub const MAX_VALUES: usize = 100;
+
+/// What the client has typed so far.
+#[derive(Clone, Debug, Default)]
+pub struct CompletionRequest {
+    /// The prompt argument or template variable being completed.
+    pub argument: String,
+    /// Its partial value.
+    pub value: String,
+    /// Values of the other arguments, as far as the client knows them.
+    pub context: HashMap<String, String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Completion {
+    pub values: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    pub has_more: bool,
+}
+
+#[async_trait]
+pub trait CompletionProvider: Send + Sync {
+    /// Candidate values for `req.argument`. They need not be filtered; the
+    /// server ranks them against `req.value`.
+    async fn complete(&self, ctx: &ToolCtx, req: &CompletionRequest) -> Vec<String>;
+}
+
+/// Providers by argument name, as declared with `complete(arg = provider_fn)`.
+pub type DeclaredProviders = Vec<(&'static str, Arc<dyn CompletionProvider>)>;
+
+/// A fixed set of values, e.g. the variants of an enum.
+pub struct Choices(pub Vec<String>);
+
+#[async_trait]
+impl CompletionProvider for Choices {
+    async fn complete(&self, _ctx: &ToolCtx, _req: &CompletionRequest) -> Vec<String> {
+        self.0.clone()
+    }
+}
+
+struct FnProvider<F>(F);
+
+#[async_trait]
+impl<F, Fut> CompletionProvider for FnProvider<F>
+where
+    F: Fn(CompletionRequest) -> Fut + Send + Sync,
+    Fut: Future<Output = Vec<String>> + Send,
+{
+    async fn complete(&self, _ctx: &ToolCtx, req: &CompletionRequest) -> Vec<String> {
+        (self.0)(req.clone()).await
+    }
+}
+
+/// A provider from an async fn or closure taking the [`CompletionRequest`].
+pub fn from_fn<F, Fut>(f: F) -> Arc<dyn CompletionProvider>
+where
+    F: Fn(CompletionRequest) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Vec<String>> + Send + 'static,
+{
+    Arc::new(FnProvider(f))
+}
+
+/// Keep the candidates matching `value`, prefix matches first, each group in
+/// the provider's order; cut at [`MAX_VALUES`].
+pub fn rank(candidates: Vec<String>, value: &str) -> Completion {
+    let needle = value.to_lowercase();
+    let (mut values, rest): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .filter(|c| c.to_lowercase().contains(&needle))
+        .partition(|c| c.to_lowercase().starts_with(&needle));
+    values.extend(rest);
+    let total = values.len();
+    values.truncate(MAX_VALUES);
+    Completion { has_more: total > values.len(), total: Some(total), values }
+}
+
+/// The allowed values of every enum-typed property of an object schema.
+pub fn schema_choices(schema: &RootSchema) -> HashMap<String, Vec<String>> {
+    let root = schema.as_value();
+    let Some(properties) = root["properties"].as_object() else {
+        return HashMap::new();
+    };
+    properties
+        .iter()
+        .filter_map(|(name, prop)| {
+            let mut values = Vec::new();
+            collect_enum(root, prop, &mut HashSet::new(), &mut values);
+            (!values.is_empty()).then(|| (name.clone(), values))
+        })
+        .collect()
+}
+
+/// String `enum`/`const` values, following local `$ref`s and `anyOf`/`oneOf`
+/// (which is how `Option<Enum>` and documented variants come out). Each
+/// `$ref` is followed once, so recursive schemas terminate.
+fn collect_enum<'a>(root: &'a Value, schema: &'a Value, seen: &mut HashSet<&'a str>, out: &mut Vec<String>) {
+    if let Some(target) = schema["$ref"].as_str().and_then(|r| r.strip_prefix('#')) {
+        if seen.insert(target)
+            && let Some(def) = root.pointer(target)
+        {
+            collect_enum(root, def, seen, out);
+        }
+        return;
+    }
+    if let Some(values) = schema["enum"].as_array() {
+        out.extend(values.iter().filter_map(Value::as_str).map(str::to_string));
+    }
+    if let Some(value) = schema["const"].as_str() {
+        out.push(value.to_string());
+    }
+    for key in ["anyOf", "oneOf"] {
+        for sub in schema[key].as_array().into_iter().flatten() {
+            collect_enum(root, sub, seen, out);
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum Reference {
+    #[serde(rename = "ref/prompt")]
+    Prompt { name: String },
+    #[serde(rename = "ref/resource")]
+    Resource { uri: String },
+}
+
+#[derive(Deserialize)]
+struct ArgumentParams {
+    name: String,
+    value: String,
+}
+
+#[derive(Default, Deserialize)]
+struct ContextParams {
+    #[serde(default)]
+    arguments: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct CompleteParams {
+    #[serde(rename = "ref")]
+    reference: Reference,
+    argument: ArgumentParams,
+    #[serde(default)]
+    context: ContextParams,
+}
+
+/// Serve `completion/complete`; shared by the transports.
+pub(crate) async fn dispatch(
+    prompts: Option<&PromptRegistry>,
+    resources: Option<&ResourceRegistry>,
+    params: Option<Value>,
+) -> Result<Value, ErrorObject> {
+    let CompleteParams { reference, argument, context } = jsonrpc::parse_params(params)?;
+    let (provider, app_state) = match reference {
+        Reference::Prompt { name } => {
+            let prompts = prompts.ok_or_else(|| ErrorObject::invalid_params("no prompts"))?;
+            let d = prompts
+                .get(&name)
+                .await
+                .ok_or_else(|| ErrorObject::invalid_params(format!("unknown prompt '{name}'")))?;
+            (d.completions.get(&argument.name).cloned(), prompts.app_state())
+        }
+        Reference::Resource { uri } => {
+            let resources = resources.ok_or_else(|| ErrorObject::invalid_params("no resources"))?;
+            let d = resources
+                .template(&uri)
+                .await
+                .ok_or_else(|| ErrorObject::invalid_params(format!("unknown resource template '{uri}'")))?;
+            (d.completions.get(&argument.name).cloned(), resources.app_state())
+        }
+    };
+    let completion = match provider {
+        Some(provider) => {
+            let req = CompletionRequest {
+                argument: argument.name,
+                value: argument.value,
+                context: context.arguments,
+            };
+            let candidates = provider.complete(&ToolCtx::new(app_state), &req).await;
+            rank(candidates, &req.value)
+        }
+        None => Completion::default(),
+    };
+    Ok(json!({"completion": completion}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn prefix_matches_rank_first() {
+        let c = rank(strings(&["typescript", "python", "Pyret", "rust"]), "py");
+        assert_eq!(c.values, ["python", "Pyret"]);
+        let c = rank(strings(&["javascript", "scala", "script"]), "sc");
+        assert_eq!(c.values, ["scala", "script", "javascript"]);
+        assert_eq!((c.total, c.has_more), (Some(3), false));
+
+        let many: Vec<String> = (0..150).map(|i| format!("v{i}")).collect();
+        let c = rank(many, "");
+        assert_eq!((c.values.len(), c.total, c.has_more), (MAX_VALUES, Some(150), true));
+    }
+
+    #[test]
+    fn enums_in_schemas_become_choices() {
+        #[derive(schemars::JsonSchema)]
+        #[allow(dead_code)]
+        enum Lang {
+            Rust,
+            Go,
+        }
+        #[derive(schemars::JsonSchema)]
+        #[allow(dead_code)]
+        struct Args {
+            lang: Lang,
+            other: Option<Lang>,
+            free: String,
+        }
+        let choices = schema_choices(&schemars::schema_for!(Args));
+        assert_eq!(choices.len(), 2);
+        assert_eq!(choices["lang"], ["Rust", "Go"]);
+        assert_eq!(choices["other"], ["Rust", "Go"]);
+    }
+
+    #[test]
+    fn recursive_schemas_terminate() {
+        #[derive(schemars::JsonSchema)]
+        #[allow(dead_code)]
+        enum Lang {
+            Rust,
+            Go,
+        }
+        #[derive(schemars::JsonSchema)]
+        #[serde(untagged)]
+        #[allow(dead_code)]
+        enum Nested {
+            Lang(Lang),
+            Boxed(Box<Nested>),
+        }
+        #[derive(schemars::JsonSchema)]
+        #[allow(dead_code)]
+        struct Args {
+            lang: Nested,
+        }
+        let choices = schema_choices(&schemars::schema_for!(Args));
+        assert_eq!(choices["lang"], ["Rust", "Go"]);
+    }
+}
@@ -0,0 +1,150 @@
+//! Types for `elicitation/create`: asking the user for structured input.
+//!
+//! The client renders a form from a flat object schema whose properties are
+//! strings, numbers, booleans or string enums, and answers with the action the
+//! user took.
+
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use schemars::generate::SchemaSettings;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+pub const ELICIT: &str = "elicitation/create";
+
+/// A person is answering, so wait longer than for other client requests.
+pub const ELICIT_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitRequest {
+    pub message: String,
+    pub requested_schema: Value,
+}
+
+impl ElicitRequest {
+    pub fn new<T: JsonSchema>(message: impl Into<String>) -> Self {
+        Self { message: message.into(), requested_schema: requested_schema::<T>() }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElicitAction {
+    Accept,
+    Decline,
+    Cancel,
+}
+
+/// The client's raw answer; see [`Elicitation`] for the typed form.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ElicitResult {
+    pub action: ElicitAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<Map<String, Value>>,
+}
+
+/// What the user did with an elicitation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Elicitation<T> {
+    /// Submitted the form.
+    Accept(T),
+    /// Explicitly said no.
+    Decline,
+    /// Dismissed the form without choosing.
+    Cancel,
+}
+
+impl<T> Elicitation<T> {
+    pub fn accepted(self) -> Option<T> {
+        match self {
+            Elicitation::Accept(t) => Some(t),
+            _ => None,
+        }
+    }
+}
+
+/// The schema of `T` in the flat shape elicitation allows.
+///
+/// Subschemas are inlined and optional fields are simply not required, since
+/// clients don't accept `$ref` or nullable types.
+pub fn requested_schema<T: JsonSchema>() -> Value {
+    let schema = SchemaSettings::draft07()
+        .with(|s| s.inline_subschemas = true)
+        .into_generator()
+        .into_root_schema_for::<T>();
+    let root = serde_json::to_value(schema).unwrap_or_default();
+    let properties: Map<String, Value> = root
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|props| props.iter().map(|(k, v)| (k.clone(), strip_null(v.clone()))).collect())
+        .unwrap_or_default();
+    let mut out = serde_json::json!({"type": "object", "properties": properties});
+    if let Some(required) = root.get("required") {
+        out["required"] = required.clone();
+    }
+    out
+}
+
+/// `Option<T>` comes out as `T | null`; keep only the `T`.
+fn strip_null(mut prop: Value) -> Value {
+    let is_null = |v: &Value| v == "null" || v.get("type").is_some_and(|t| t == "null");
+    if let Some(Value::Array(types)) = prop.get_mut("type") {
+        types.retain(|t| t != "null");
+        if types.len() == 1 {
+            prop["type"] = types.pop().unwrap();
+        }
+    }
+    if let Some(Value::Array(values)) = prop.get_mut("enum") {
+        values.retain(|v| !v.is_null());
+    }
+    if let Some(Value::Array(any_of)) = prop.get("anyOf")
+        && any_of.len() == 2
+        && let Some(inner) = any_of.iter().find(|v| !is_null(v))
+        && any_of.iter().any(is_null)
+    {
+        let mut inner = inner.clone();
+        if let (Some(outer), Some(fields)) = (prop.as_object(), inner.as_object_mut()) {
+            for (k, v) in outer.iter().filter(|(k, _)| *k != "anyOf") {
+                fields.entry(k.clone()).or_insert_with(|| v.clone());
+            }
+        }
+        return inner;
+    }
+    prop
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    enum Size {
+        Small,
+        Large,
+    }
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    struct Order {
+        /// Number of items
+        count: u32,
+        size: Option<Size>,
+        note: Option<String>,
+    }
+
+    #[test]
+    fn schemas_are_flat_and_inline() {
+        let schema = requested_schema::<Order>();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], json!(["count"]));
+        let props = &schema["properties"];
+        assert_eq!(props["count"]["description"], "Number of items");
+        assert_eq!(props["size"]["enum"], json!(["Small", "Large"]));
+        assert_eq!(props["note"]["type"], "string");
+        assert!(!schema.to_string().contains("$ref"));
+    }
+}
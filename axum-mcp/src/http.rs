@@ -1,20 +1,24 @@
 use axum::Json;
 use axum::body::Body;
-use axum::http::{Request, StatusCode};
+use axum::http::{HeaderMap, HeaderValue, Request, StatusCode};
 use axum::response::{
     Response,
     sse::{Event, Sse},
 };
-use futures::stream;
+use futures::{Stream, StreamExt, stream};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::mpsc;
 
+use crate::jsonrpc::{self, ErrorObject, Message, ResponsePayload};
+use crate::layer::McpLayerConfig;
 use crate::registry::ToolRegistry;
-use crate::security::{
-    AllowedOrigins, Auth, VersionPolicy, has_valid_protocol_version_with, is_authorized,
-    is_origin_allowed,
-};
+use crate::server::McpServer;
+use crate::session::{SESSION_HEADER, Session, SessionManager};
+use crate::security::{has_valid_protocol_version_with, is_authorized, is_origin_allowed};
 
 #[derive(Deserialize)]
 struct RawOp {
@@ -25,6 +29,7 @@ struct RawOp {
     args: Value,
 }
 
+/// A tool as the legacy `{"op": "tools/list"}` body lists it.
 #[derive(Serialize)]
 struct ToolMeta {
     name: String,
@@ -36,34 +41,60 @@ struct ToolMeta {
     structured: bool,
 }
 
+fn json_response<T: Serialize>(status: StatusCode, body: &T) -> Response {
+    axum::response::Response::builder()
+        .status(status)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(body).unwrap()))
+        .unwrap()
+}
+
+fn plain_response(status: StatusCode, body: &'static str) -> Response {
+    axum::response::Response::builder()
+        .status(status)
+        .body(Body::from(body))
+        .unwrap()
+}
+
+/// An early plain-text refusal, turned into a response by [`plain_response`].
+type Rejection = (StatusCode, &'static str);
+
+fn check_origin_and_auth(headers: &HeaderMap, config: &McpLayerConfig) -> Result<(), Rejection> {
+    if !is_origin_allowed(headers, config.allowed_origins) {
+        return Err((StatusCode::FORBIDDEN, "forbidden origin"));
+    }
+    if !is_authorized(headers, &config.auth) {
+        return Err((StatusCode::UNAUTHORIZED, "unauthorized"));
+    }
+    Ok(())
+}
+
+/// Resolve the `Mcp-Session-Id` header: 400 when absent, 404 when unknown or expired.
+fn session_from_headers(
+    headers: &HeaderMap,
+    sessions: &SessionManager,
+) -> Result<Arc<Session>, Rejection> {
+    let Some(id) = headers.get(SESSION_HEADER).and_then(|v| v.to_str().ok()) else {
+        return Err((StatusCode::BAD_REQUEST, "missing Mcp-Session-Id"));
+    };
+    sessions.get(id).ok_or((StatusCode::NOT_FOUND, "unknown session"))
+}
+
 pub async fn handle_post(
     req: Request<Body>,
-    registry: &ToolRegistry,
-    allowed: AllowedOrigins,
-    auth: Auth,
-    version_policy: VersionPolicy,
+    registry: &Arc<ToolRegistry>,
+    config: &McpLayerConfig,
+    sessions: &SessionManager,
 ) -> Response {
     // Security checks
-    if !has_valid_protocol_version_with(req.headers(), &version_policy) {
-        return axum::response::Response::builder()
-            .status(StatusCode::BAD_REQUEST)
-            .body(Body::from("missing/invalid MCP-Protocol-Version"))
-            .unwrap();
-    }
-    if !is_origin_allowed(req.headers(), allowed) {
-        return axum::response::Response::builder()
-            .status(StatusCode::FORBIDDEN)
-            .body(Body::from("forbidden origin"))
-            .unwrap();
-    }
-    if !is_authorized(req.headers(), &auth) {
-        return axum::response::Response::builder()
-            .status(StatusCode::UNAUTHORIZED)
-            .body(Body::from("unauthorized"))
-            .unwrap();
-    }
-
-    let (_parts, body) = req.into_parts();
+    if !has_valid_protocol_version_with(req.headers(), &config.version_policy) {
+        return plain_response(StatusCode::BAD_REQUEST, "missing/invalid MCP-Protocol-Version");
+    }
+    if let Err((status, msg)) = check_origin_and_auth(req.headers(), config) {
+        return plain_response(status, msg);
+    }
+
+    let (parts, body) = req.into_parts();
     let bytes = match axum::body::to_bytes(body, 1 << 20).await {
         Ok(b) => b,
         Err(_) => {
@@ -73,7 +104,202 @@ pub async fn handle_post(
             ));
         }
     };
-    let raw: RawOp = match serde_json::from_slice(&bytes) {
+    let value: Value = match serde_json::from_slice(&bytes) {
+        Ok(v) => v,
+        Err(e) => {
+            let resp = jsonrpc::Response::error(None, ErrorObject::parse_error(e));
+            return json_response(StatusCode::BAD_REQUEST, &resp);
+        }
+    };
+
+    if config.legacy_ops && value.get("op").is_some() {
+        return handle_legacy_op(value, registry).await;
+    }
+    let server = McpServer::new(registry.clone())
+        .with_resources(config.resources.clone())
+        .with_prompts(config.prompts.clone())
+        .with_server_info(config.server_info.clone())
+        .with_page_size(config.page_size)
+        .with_standalone_stream(config.enable_sse && config.stateful);
+    if let Value::Array(items) = value {
+        return handle_batch(items, &parts.headers, &server, config, sessions).await;
+    }
+
+    let msg = match Message::parse(value) {
+        Ok(m) => m,
+        Err(resp) => return json_response(StatusCode::BAD_REQUEST, &resp),
+    };
+
+    // `initialize` opens a session; everything else must name a live one.
+    if config.stateful
+        && let Message::Request(req) = &msg
+        && req.method == "initialize"
+    {
+        return handle_initialize(req.clone(), &server, sessions).await;
+    }
+    let session = if config.stateful {
+        match session_from_headers(&parts.headers, sessions) {
+            Ok(s) => Some(s),
+            Err((status, msg)) => return plain_response(status, msg),
+        }
+    } else {
+        None
+    };
+
+    if let Message::Request(req) = &msg
+        && config.stream_responses
+        && req.method == "tools/call"
+        && accepts_sse(&parts.headers)
+    {
+        return streamed_response(&server, config, session, req.clone());
+    }
+    match server.handle(session, msg, None).await {
+        Some(resp) => json_response(StatusCode::OK, &resp),
+        None => plain_response(StatusCode::ACCEPTED, ""),
+    }
+}
+
+/// A JSON-RPC batch: members run concurrently and their responses come back
+/// as one array, or `202` when nothing in it needs an answer.
+async fn handle_batch(
+    items: Vec<Value>,
+    headers: &HeaderMap,
+    server: &McpServer,
+    config: &McpLayerConfig,
+    sessions: &SessionManager,
+) -> Response {
+    if items.is_empty() || items.len() > config.max_batch_size {
+        let err = ErrorObject::invalid_request(format!(
+            "batch must hold 1 to {} messages",
+            config.max_batch_size
+        ));
+        return json_response(StatusCode::BAD_REQUEST, &jsonrpc::Response::error(None, err));
+    }
+    let session = if config.stateful {
+        match session_from_headers(headers, sessions) {
+            Ok(s) => Some(s),
+            Err((status, msg)) => return plain_response(status, msg),
+        }
+    } else {
+        None
+    };
+
+    let mut replies = Vec::new();
+    let mut pending = Vec::new();
+    for item in items {
+        match Message::parse(item) {
+            Ok(Message::Request(req)) if req.method == "initialize" => {
+                let err = ErrorObject::invalid_request("initialize must not be batched");
+                replies.push(jsonrpc::Response::error(Some(req.id), err));
+            }
+            Ok(msg) => pending.push(server.handle(session.clone(), msg, None)),
+            Err(resp) => replies.push(resp),
+        }
+    }
+    replies.extend(futures::future::join_all(pending).await.into_iter().flatten());
+
+    if replies.is_empty() {
+        plain_response(StatusCode::ACCEPTED, "")
+    } else {
+        json_response(StatusCode::OK, &replies)
+    }
+}
+
+/// True when the `Accept` header lists `text/event-stream` (with a non-zero q).
+fn accepts_sse(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(axum::http::header::ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|item| {
+            let mut parts = item.split(';').map(str::trim);
+            let media = parts.next().unwrap_or_default();
+            let refused = parts.any(|p| {
+                p.strip_prefix("q=").and_then(|q| q.parse::<f32>().ok()) == Some(0.0)
+            });
+            media.eq_ignore_ascii_case("text/event-stream") && !refused
+        })
+}
+
+/// Answer a request with an SSE stream: every message the handler emits while
+/// running, then the JSON-RPC response, then end of stream.
+///
+/// The call is driven by the response body itself, so it stops when the
+/// client goes away.
+fn streamed_response(
+    server: &McpServer,
+    config: &McpLayerConfig,
+    session: Option<Arc<Session>>,
+    req: jsonrpc::Request,
+) -> Response {
+    let (tx, rx) = mpsc::unbounded_channel::<Value>();
+    let handled = server.handle(session, Message::Request(req), Some(tx.clone()));
+    let call = async move {
+        if let Some(response) = handled.await {
+            let _ = tx.send(serde_json::to_value(response).unwrap());
+        }
+    };
+    // The channel closes once the call (and its context) dropped every sender,
+    // which is right after the response was queued.
+    let messages = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|m| (m, rx)) });
+    let driver = stream::once(call).filter_map(|()| async { None });
+    let events = stream::select(messages, driver)
+        .map(|m| Ok::<_, Infallible>(Event::default().data(m.to_string())));
+    axum::response::IntoResponse::into_response(
+        Sse::new(events).keep_alive(
+            axum::response::sse::KeepAlive::new()
+                .interval(config.sse_keep_alive)
+                .text("ping"),
+        ),
+    )
+}
+
+async fn handle_initialize(
+    req: jsonrpc::Request,
+    server: &McpServer,
+    sessions: &SessionManager,
+) -> Response {
+    let session = sessions.create();
+    let handled = server.handle(Some(session.clone()), Message::Request(req), None);
+    let Some(response) = handled.await else {
+        sessions.remove(session.id());
+        return plain_response(StatusCode::ACCEPTED, "");
+    };
+    if let ResponsePayload::Error(_) = &response.payload {
+        sessions.remove(session.id());
+        return json_response(StatusCode::OK, &response);
+    }
+    let mut resp = json_response(StatusCode::OK, &response);
+    resp.headers_mut()
+        .insert(SESSION_HEADER, HeaderValue::from_str(session.id()).unwrap());
+    resp
+}
+
+/// `DELETE` ends a session explicitly.
+pub async fn handle_delete(
+    req: Request<Body>,
+    config: &McpLayerConfig,
+    sessions: &SessionManager,
+) -> Response {
+    if let Err((status, msg)) = check_origin_and_auth(req.headers(), config) {
+        return plain_response(status, msg);
+    }
+    if !config.stateful {
+        return plain_response(StatusCode::METHOD_NOT_ALLOWED, "sessions disabled");
+    }
+    match session_from_headers(req.headers(), sessions) {
+        Ok(session) => {
+            sessions.remove(session.id());
+            plain_response(StatusCode::NO_CONTENT, "")
+        }
+        Err((status, msg)) => plain_response(status, msg),
+    }
+}
+
+/// Serves the pre-JSON-RPC `{"op": ...}` bodies when `McpLayerConfig::legacy_ops` is set.
+async fn handle_legacy_op(value: Value, registry: &ToolRegistry) -> Response {
+    let raw: RawOp = match serde_json::from_value(value) {
         Ok(v) => v,
         Err(e) => {
             return axum::response::IntoResponse::into_response((
@@ -85,14 +311,15 @@ pub async fn handle_post(
 
     match raw.op.as_str() {
         "tools/list" => {
-            let list = registry.list().await;
-            let tools: Vec<_> = list
+            let tools: Vec<_> = registry
+                .descriptors()
+                .await
                 .into_iter()
-                .map(|(name, desc, i, o)| ToolMeta {
-                    name,
-                    desc,
-                    input_schema: i,
-                    output_schema: o,
+                .map(|d| ToolMeta {
+                    name: d.name.to_string(),
+                    desc: d.description,
+                    input_schema: d.input_schema,
+                    output_schema: d.output_schema,
                     structured: true,
                 })
                 .collect();
@@ -108,23 +335,33 @@ pub async fn handle_post(
                     ));
                 }
             };
-            match registry.call(&name, raw.args).await {
-                Ok(v) => axum::response::IntoResponse::into_response(Json(
-                    json!({"ok": true, "result": v}),
-                )),
+            match registry.call_raw(&name, raw.args).await {
+                Ok(r) if !r.is_error => {
+                    // What the handler returned: its value, or else its text.
+                    let v = match (r.structured_content, r.content.as_slice()) {
+                        (Some(v), _) => v,
+                        (None, [crate::content::ContentBlock::Text { text }]) => json!(text),
+                        (None, content) => json!(content),
+                    };
+                    axum::response::IntoResponse::into_response(Json(json!({"ok": true, "result": v})))
+                }
+                Ok(r) => {
+                    let message = r.content.iter().find_map(|c| match c {
+                        crate::content::ContentBlock::Text { text } => Some(text.clone()),
+                        _ => None,
+                    });
+                    let body = json!({"ok": false, "code": "internal", "message": message});
+                    json_response(StatusCode::INTERNAL_SERVER_ERROR, &body)
+                }
                 Err(e) => {
                     use crate::tool::ToolError::*;
                     let (code, status) = match &e {
                         NotFound(_) => ("tool_not_found", StatusCode::NOT_FOUND),
                         InvalidArgs(_) => ("invalid_args", StatusCode::BAD_REQUEST),
-                        Internal(_) => ("internal", StatusCode::INTERNAL_SERVER_ERROR),
+                        Internal(_) | Cancelled => ("internal", StatusCode::INTERNAL_SERVER_ERROR),
                     };
                     let body = json!({"ok": false, "code": code, "message": e.to_string()});
-                    axum::response::Response::builder()
-                        .status(status)
-                        .header(axum::http::header::CONTENT_TYPE, "application/json")
-                        .body(Body::from(serde_json::to_vec(&body).unwrap()))
-                        .unwrap()
+                    json_response(status, &body)
                 }
             }
         }
@@ -132,24 +369,68 @@ pub async fn handle_post(
     }
 }
 
+/// `GET` opens the session's server-to-client SSE stream.
+///
+/// Each event carries its outbox id; a client reconnecting with
+/// `Last-Event-ID` first receives the buffered events it missed.
 pub async fn handle_sse_get(
-    registry: &ToolRegistry,
-    _allowed: AllowedOrigins,
-    _auth: Auth,
+    req: Request<Body>,
+    config: &McpLayerConfig,
+    sessions: &SessionManager,
 ) -> Response {
-    // Simple one-shot SSE announcing readiness and available tool count
-    let count = registry.list().await.len();
-    let fut = async move {
-        let ready = json!({"event":"ready","tool_count": count});
-        let ev = Event::default().data(serde_json::to_string(&ready).unwrap());
-        Ok::<_, std::convert::Infallible>(ev)
+    if let Err((status, msg)) = check_origin_and_auth(req.headers(), config) {
+        return plain_response(status, msg);
+    }
+    if !config.enable_sse {
+        return plain_response(StatusCode::METHOD_NOT_ALLOWED, "SSE disabled");
+    }
+    if !config.stateful {
+        return plain_response(StatusCode::METHOD_NOT_ALLOWED, "sessions disabled");
+    }
+    let session = match session_from_headers(req.headers(), sessions) {
+        Ok(s) => s,
+        Err((status, msg)) => return plain_response(status, msg),
     };
-    let stream = stream::once(fut);
+    let last_event_id = req
+        .headers()
+        .get(LAST_EVENT_ID)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    let retry = config.sse_retry.map(|d| Ok(Event::default().retry(d)));
+    let stream = stream::iter(retry).chain(session_events(session, last_event_id));
     axum::response::IntoResponse::into_response(
         Sse::new(stream).keep_alive(
             axum::response::sse::KeepAlive::new()
-                .interval(Duration::from_secs(15))
+                .interval(config.sse_keep_alive)
                 .text("ping"),
         ),
     )
 }
+
+const LAST_EVENT_ID: &str = "Last-Event-ID";
+
+fn session_events(
+    session: Arc<Session>,
+    last_event_id: Option<u64>,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    let (replay, rx) = session.outbox().subscribe(last_event_id);
+    let last = last_event_id.unwrap_or(0);
+    stream::unfold(
+        (session, VecDeque::from(replay), rx, last),
+        |(session, mut replay, mut rx, mut last)| async move {
+            loop {
+                let event = match replay.pop_front() {
+                    Some(e) => e,
+                    None => rx.recv().await?,
+                };
+                if event.id <= last {
+                    continue;
+                }
+                last = event.id;
+                session.outbox().mark_delivered(event.id);
+                let sse = Event::default().id(event.id.to_string()).data(&*event.data);
+                return Some((Ok(sse), (session, replay, rx, last)));
+            }
+        },
+    )
+}
@@ -0,0 +1,247 @@
+//! JSON-RPC 2.0 framing shared by the MCP transports.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::tool::ToolError;
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+pub mod error_codes {
+    pub const PARSE_ERROR: i32 = -32700;
+    pub const INVALID_REQUEST: i32 = -32600;
+    pub const METHOD_NOT_FOUND: i32 = -32601;
+    pub const INVALID_PARAMS: i32 = -32602;
+    pub const INTERNAL_ERROR: i32 = -32603;
+    /// Not part of JSON-RPC itself; answered to requests sent before `initialize`.
+    /// Distinct from [`RESOURCE_NOT_FOUND`], which MCP fixes at `-32002`.
+    pub const SERVER_NOT_INITIALIZED: i32 = -32003;
+    /// Marks the outcome of a cancelled request; transports send no response for it.
+    pub const REQUEST_CANCELLED: i32 = -32800;
+    /// MCP: `resources/read` for a URI nothing serves.
+    pub const RESOURCE_NOT_FOUND: i32 = -32002;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RequestId {
+    Number(i64),
+    String(String),
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestId::Number(n) => write!(f, "{n}"),
+            RequestId::String(s) => f.write_str(s),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Request {
+    pub jsonrpc: String,
+    pub id: RequestId,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Notification {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+impl Notification {
+    pub fn new(method: impl Into<String>, params: Option<Value>) -> Self {
+        Self { jsonrpc: JSONRPC_VERSION.to_string(), method: method.into(), params }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ErrorObject {
+    pub code: i32,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl ErrorObject {
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        Self { code, message: message.into(), data: None }
+    }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    pub fn parse_error(message: impl std::fmt::Display) -> Self {
+        Self::new(error_codes::PARSE_ERROR, format!("parse error: {message}"))
+    }
+
+    pub fn invalid_request(message: impl std::fmt::Display) -> Self {
+        Self::new(error_codes::INVALID_REQUEST, format!("invalid request: {message}"))
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(error_codes::METHOD_NOT_FOUND, format!("method not found: {method}"))
+    }
+
+    pub fn invalid_params(message: impl std::fmt::Display) -> Self {
+        Self::new(error_codes::INVALID_PARAMS, format!("invalid params: {message}"))
+    }
+
+    pub fn internal(message: impl std::fmt::Display) -> Self {
+        Self::new(error_codes::INTERNAL_ERROR, format!("internal error: {message}"))
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.code == error_codes::REQUEST_CANCELLED
+    }
+}
+
+impl From<ToolError> for ErrorObject {
+    fn from(e: ToolError) -> Self {
+        let code = match &e {
+            // MCP reports unknown tools as invalid params of `tools/call`.
+            ToolError::NotFound(_) | ToolError::InvalidArgs(_) => error_codes::INVALID_PARAMS,
+            ToolError::Internal(_) => error_codes::INTERNAL_ERROR,
+            ToolError::Cancelled => error_codes::REQUEST_CANCELLED,
+        };
+        Self::new(code, e.to_string())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponsePayload {
+    Result(Value),
+    Error(ErrorObject),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Response {
+    pub jsonrpc: String,
+    /// `None` serializes as `null`, used when the request id could not be read.
+    pub id: Option<RequestId>,
+    #[serde(flatten)]
+    pub payload: ResponsePayload,
+}
+
+impl Response {
+    pub fn result(id: RequestId, result: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: Some(id),
+            payload: ResponsePayload::Result(result),
+        }
+    }
+
+    pub fn error(id: Option<RequestId>, error: ErrorObject) -> Self {
+        Self { jsonrpc: JSONRPC_VERSION.to_string(), id, payload: ResponsePayload::Error(error) }
+    }
+
+    pub fn from_result(id: RequestId, result: Result<Value, ErrorObject>) -> Self {
+        match result {
+            Ok(v) => Self::result(id, v),
+            Err(e) => Self::error(Some(id), e),
+        }
+    }
+}
+
+/// A single decoded JSON-RPC message.
+#[derive(Clone, Debug)]
+pub enum Message {
+    Request(Request),
+    Notification(Notification),
+    Response(Response),
+}
+
+impl Message {
+    /// Classify a JSON value as a request, notification or response.
+    ///
+    /// On failure the ready-to-send `-32600` error response is returned, carrying
+    /// the request id when one could be recovered.
+    pub fn parse(value: Value) -> Result<Message, Response> {
+        let id = value
+            .get("id")
+            .and_then(|v| serde_json::from_value::<RequestId>(v.clone()).ok());
+        let invalid = |msg: &str| Response::error(id.clone(), ErrorObject::invalid_request(msg));
+        let Some(obj) = value.as_object() else {
+            return Err(invalid("expected a JSON object"));
+        };
+        if obj.get("jsonrpc").and_then(Value::as_str) != Some(JSONRPC_VERSION) {
+            return Err(invalid("jsonrpc must be \"2.0\""));
+        }
+        if obj.contains_key("method") {
+            if obj.contains_key("id") {
+                serde_json::from_value(value.clone())
+                    .map(Message::Request)
+                    .map_err(|e| invalid(&e.to_string()))
+            } else {
+                serde_json::from_value(value.clone())
+                    .map(Message::Notification)
+                    .map_err(|e| invalid(&e.to_string()))
+            }
+        } else if obj.contains_key("result") || obj.contains_key("error") {
+            serde_json::from_value(value.clone())
+                .map(Message::Response)
+                .map_err(|e| invalid(&e.to_string()))
+        } else {
+            Err(invalid("missing method"))
+        }
+    }
+}
+
+/// The `_meta` object a client may attach to request params.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct RequestMeta {
+    /// Opaque token (string or number) to tag `notifications/progress` with.
+    #[serde(rename = "progressToken")]
+    pub progress_token: Option<Value>,
+}
+
+/// Deserialize request params, treating absent params as an empty object.
+pub fn parse_params<T: DeserializeOwned>(params: Option<Value>) -> Result<T, ErrorObject> {
+    let params = match params {
+        Some(Value::Null) | None => Value::Object(Map::new()),
+        Some(v) => v,
+    };
+    serde_json::from_value(params).map_err(ErrorObject::invalid_params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn classifies_messages() {
+        let req = json!({"jsonrpc":"2.0","id":1,"method":"tools/list"});
+        assert!(matches!(Message::parse(req), Ok(Message::Request(r)) if r.id == RequestId::Number(1)));
+        let note = json!({"jsonrpc":"2.0","method":"notifications/initialized"});
+        assert!(matches!(Message::parse(note), Ok(Message::Notification(_))));
+        let resp = json!({"jsonrpc":"2.0","id":"a","result":{}});
+        assert!(matches!(Message::parse(resp), Ok(Message::Response(_))));
+    }
+
+    #[test]
+    fn rejects_invalid_requests_with_id() {
+        let err = Message::parse(json!({"jsonrpc":"1.0","id":7,"method":"x"})).unwrap_err();
+        assert_eq!(err.id, Some(RequestId::Number(7)));
+        assert!(matches!(err.payload, ResponsePayload::Error(e) if e.code == error_codes::INVALID_REQUEST));
+    }
+
+    #[test]
+    fn response_shape() {
+        let ok = serde_json::to_value(Response::result(RequestId::Number(1), json!({"x":1}))).unwrap();
+        assert_eq!(ok, json!({"jsonrpc":"2.0","id":1,"result":{"x":1}}));
+        let err = serde_json::to_value(Response::error(None, ErrorObject::new(-1, "boom"))).unwrap();
+        assert_eq!(err, json!({"jsonrpc":"2.0","id":null,"error":{"code":-1,"message":"boom"}}));
+    }
+}
@@ -1,12 +1,19 @@
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use axum::body::Body;
 use axum::http::{Method, Request, Response};
 use tower::{Layer, Service};
 
-use crate::http::{handle_post, handle_sse_get};
+use crate::http::{handle_delete, handle_post, handle_sse_get};
+use crate::lifecycle::ServerInfo;
+use crate::outbound::DEFAULT_EVENT_BUFFER;
+use crate::pagination::DEFAULT_PAGE_SIZE;
 use crate::registry::ToolRegistry;
+use crate::prompt::{self, PromptRegistry};
+use crate::resource::{self, ResourceRegistry};
+use crate::session::SessionManager;
 use crate::security::{
     AllowedOrigins, Auth, FALLBACK_PROTOCOL_VERSION, REQUIRED_PROTOCOL_VERSION, VersionPolicy,
 };
@@ -16,9 +23,40 @@ pub struct McpLayerConfig {
     pub path: &'static str,
     pub require_version: bool,
     pub allowed_origins: AllowedOrigins,
+    /// Serve the `GET` SSE stream that carries server-initiated messages.
+    /// Without it list changes and resource updates are not advertised, and
+    /// requests to the client outside a streamed response fail at once.
     pub enable_sse: bool,
     pub auth: Auth,
     pub version_policy: VersionPolicy,
+    /// Also accept the pre-JSON-RPC `{"op": ...}` request bodies on POST.
+    /// Off by default, so callers still sending them must turn it on;
+    /// deprecated and to be removed in a later release.
+    pub legacy_ops: bool,
+    pub server_info: ServerInfo,
+    /// Issue `Mcp-Session-Id` on `initialize` and require it afterwards.
+    pub stateful: bool,
+    /// Idle sessions older than this are dropped and answered with 404.
+    pub session_ttl: Duration,
+    /// Interval of the keep-alive comments on SSE streams.
+    pub sse_keep_alive: Duration,
+    /// Outbound events kept per session for `Last-Event-ID` replay.
+    pub sse_buffer: usize,
+    /// Reconnect delay suggested to clients in the first SSE event.
+    pub sse_retry: Option<Duration>,
+    /// Answer `tools/call` as an SSE stream when the client accepts one, so
+    /// notifications emitted by the tool arrive before the result.
+    pub stream_responses: bool,
+    /// Most messages accepted in one JSON-RPC batch; `0` turns batches away.
+    pub max_batch_size: usize,
+    /// Items per `*/list` page; `0` returns everything at once.
+    pub page_size: usize,
+    /// Resources served next to the tools. `McpLayer::new` fills in every
+    /// `#[mcp_resource]` in the binary when `None`, as `run_stdio` does; pass
+    /// an empty registry to serve none.
+    pub resources: Option<Arc<ResourceRegistry>>,
+    /// Prompts served next to the tools; gathered like `resources`.
+    pub prompts: Option<Arc<PromptRegistry>>,
 }
 
 impl Default for McpLayerConfig {
@@ -27,12 +65,24 @@ impl Default for McpLayerConfig {
             path: "/mcp",
             require_version: true,
             allowed_origins: AllowedOrigins::LocalhostOnly,
-            enable_sse: false,
+            enable_sse: true,
             auth: Auth::None,
             version_policy: VersionPolicy::AllowFallback {
                 required: REQUIRED_PROTOCOL_VERSION,
                 fallback: FALLBACK_PROTOCOL_VERSION,
             },
+            legacy_ops: false,
+            server_info: ServerInfo::default(),
+            stateful: true,
+            session_ttl: Duration::from_secs(30 * 60),
+            sse_keep_alive: Duration::from_secs(15),
+            sse_buffer: DEFAULT_EVENT_BUFFER,
+            sse_retry: Some(Duration::from_secs(3)),
+            stream_responses: true,
+            max_batch_size: 64,
+            page_size: DEFAULT_PAGE_SIZE,
+            resources: None,
+            prompts: None,
         }
     }
 }
@@ -41,11 +91,26 @@ impl Default for McpLayerConfig {
 pub struct McpLayer {
     registry: Arc<ToolRegistry>,
     config: McpLayerConfig,
+    sessions: Arc<SessionManager>,
 }
 
 impl McpLayer {
-    pub fn new(registry: Arc<ToolRegistry>, config: McpLayerConfig) -> Self {
-        Self { registry, config }
+    pub fn new(registry: Arc<ToolRegistry>, mut config: McpLayerConfig) -> Self {
+        let state = registry.app_state();
+        if config.resources.is_none() && !resource::RESOURCES.is_empty() {
+            config.resources = Some(ResourceRegistry::gather_with_state(state.clone()));
+        }
+        if config.prompts.is_none() && !prompt::PROMPTS.is_empty() {
+            config.prompts = Some(PromptRegistry::gather_with_state(state));
+        }
+        let sessions = Arc::new(
+            SessionManager::new(config.session_ttl).with_event_buffer(config.sse_buffer),
+        );
+        Self { registry, config, sessions }
+    }
+
+    pub fn sessions(&self) -> &Arc<SessionManager> {
+        &self.sessions
     }
 }
 
@@ -56,6 +121,7 @@ impl<S> Layer<S> for McpLayer {
             inner,
             registry: self.registry.clone(),
             config: self.config.clone(),
+            sessions: self.sessions.clone(),
             path: self.config.path,
         }
     }
@@ -66,6 +132,7 @@ pub struct McpService<S> {
     inner: S,
     registry: Arc<ToolRegistry>,
     config: McpLayerConfig,
+    sessions: Arc<SessionManager>,
     path: &'static str,
 }
 
@@ -88,17 +155,20 @@ where
         let method = req.method().clone();
         let mut inner = self.inner.clone();
         let registry = self.registry.clone();
-        let allowed = self.config.allowed_origins;
+        let config = self.config.clone();
+        let sessions = self.sessions.clone();
         let intercept_post = path == self.path && method == Method::POST;
-        let intercept_get = path == self.path && method == Method::GET && self.config.enable_sse;
-        let auth = self.config.auth.clone();
-        let policy = self.config.version_policy;
+        let intercept_get = path == self.path && method == Method::GET;
+        let intercept_delete = path == self.path && method == Method::DELETE;
         Box::pin(async move {
             if intercept_post {
-                let resp = handle_post(req, &registry, allowed, auth.clone(), policy).await;
+                let resp = handle_post(req, &registry, &config, &sessions).await;
+                Ok(resp)
+            } else if intercept_delete {
+                let resp = handle_delete(req, &config, &sessions).await;
                 Ok(resp)
             } else if intercept_get {
-                let resp = handle_sse_get(&registry, allowed, auth).await;
+                let resp = handle_sse_get(req, &config, &sessions).await;
                 Ok(resp)
             } else {
                 inner.call(req).await
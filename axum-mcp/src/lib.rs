@@ -1,5 +1,22 @@
 pub mod tool;
 pub mod registry;
+pub mod resource;
+pub mod prompt;
+pub mod content;
+pub mod completion;
+pub mod logging;
+pub mod peer;
+pub mod sampling;
+pub mod elicitation;
+pub mod roots;
+pub mod uri_template;
+pub mod jsonrpc;
+pub mod lifecycle;
+pub mod session;
+pub mod outbound;
+pub mod cancel;
+pub mod pagination;
+pub mod server;
 #[cfg(feature = "http")] pub mod layer;
 #[cfg(feature = "http")] pub mod http;
 #[cfg(feature = "stdio")] pub mod stdio;
@@ -7,20 +24,9 @@ pub mod security;
 pub mod prelude;
 pub mod schema;
 
-/// Helper trait for converting common HTTP return types into JSON values
-/// usable by MCP Tool handlers.
-pub trait IntoJsonValue {
-    fn into_json_value(self) -> serde_json::Value;
-}
-
-impl<T> IntoJsonValue for axum::Json<T>
-where
-    T: serde::Serialize,
-{
-    fn into_json_value(self) -> serde_json::Value {
-        serde_json::to_value(self.0).unwrap_or(serde_json::Value::Null)
-    }
-}
-
 #[cfg(feature = "http")] pub use layer::{McpLayer, McpLayerConfig};
 pub use registry::{ToolRegistry};
+pub use resource::ResourceRegistry;
+pub use prompt::PromptRegistry;
+pub use lifecycle::ServerInfo;
+pub use server::McpServer;
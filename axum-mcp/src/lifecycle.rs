@@ -0,0 +1,258 @@
+//! MCP connection lifecycle: the `initialize` handshake and capability negotiation.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::jsonrpc::{ErrorObject, error_codes};
+use crate::security::{FALLBACK_PROTOCOL_VERSION, REQUIRED_PROTOCOL_VERSION};
+
+/// Protocol versions this server can speak, newest first.
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] =
+    &[REQUIRED_PROTOCOL_VERSION, FALLBACK_PROTOCOL_VERSION];
+
+pub const INITIALIZED_NOTIFICATION: &str = "notifications/initialized";
+
+/// What the server reports about itself in the `initialize` result.
+#[derive(Clone, Debug)]
+pub struct ServerInfo {
+    pub name: String,
+    pub version: String,
+    pub instructions: Option<String>,
+}
+
+impl Default for ServerInfo {
+    fn default() -> Self {
+        Self {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            instructions: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Implementation {
+    pub name: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ToolsCapability {
+    #[serde(rename = "listChanged", skip_serializing_if = "std::ops::Not::not")]
+    pub list_changed: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ResourcesCapability {
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub subscribe: bool,
+    #[serde(rename = "listChanged", skip_serializing_if = "std::ops::Not::not")]
+    pub list_changed: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PromptsCapability {
+    #[serde(rename = "listChanged", skip_serializing_if = "std::ops::Not::not")]
+    pub list_changed: bool,
+}
+
+/// Advertised as `{}`: tools can always log to the client.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LoggingCapability {}
+
+/// Advertised as `{}` when `completion/complete` is served.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CompletionsCapability {}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ServerCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<ToolsCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourcesCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompts: Option<PromptsCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completions: Option<CompletionsCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logging: Option<LoggingCapability>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeParams {
+    pub protocol_version: String,
+    #[serde(default)]
+    pub capabilities: Value,
+    pub client_info: Implementation,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeResult {
+    pub protocol_version: String,
+    pub capabilities: ServerCapabilities,
+    pub server_info: Implementation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+}
+
+/// Pick the version to answer with: the client's if we support it, else our newest.
+pub fn negotiate_version(requested: &str) -> &'static str {
+    SUPPORTED_PROTOCOL_VERSIONS
+        .iter()
+        .find(|v| **v == requested)
+        .copied()
+        .unwrap_or(REQUIRED_PROTOCOL_VERSION)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// No `initialize` seen yet; only `initialize` and `ping` are accepted.
+    Uninitialized,
+    /// `initialize` answered, waiting for `notifications/initialized`.
+    Initializing,
+    Ready,
+}
+
+/// Per-connection handshake state.
+#[derive(Debug)]
+pub struct Lifecycle {
+    phase: Phase,
+    protocol_version: Option<&'static str>,
+    client_info: Option<Implementation>,
+    client_capabilities: Value,
+    server_capabilities: ServerCapabilities,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lifecycle {
+    pub fn new() -> Self {
+        Self {
+            phase: Phase::Uninitialized,
+            protocol_version: None,
+            client_info: None,
+            client_capabilities: Value::Null,
+            server_capabilities: ServerCapabilities::default(),
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn protocol_version(&self) -> Option<&'static str> {
+        self.protocol_version
+    }
+
+    pub fn client_info(&self) -> Option<&Implementation> {
+        self.client_info.as_ref()
+    }
+
+    pub fn client_capabilities(&self) -> &Value {
+        &self.client_capabilities
+    }
+
+    /// What we advertised in the `initialize` result; empty before it.
+    pub fn server_capabilities(&self) -> &ServerCapabilities {
+        &self.server_capabilities
+    }
+
+    /// Whether this client was promised `notifications/tools/list_changed`.
+    pub fn wants_tools_list_changed(&self) -> bool {
+        self.server_capabilities.tools.as_ref().is_some_and(|t| t.list_changed)
+    }
+
+    /// Check whether a request for `method` may be served in the current phase.
+    ///
+    /// Requests sent after `initialize` but before `notifications/initialized`
+    /// are let through, since many clients pipeline them.
+    pub fn check_request(&self, method: &str) -> Result<(), ErrorObject> {
+        match (method, self.phase) {
+            ("ping", _) => Ok(()),
+            ("initialize", Phase::Uninitialized) => Ok(()),
+            ("initialize", _) => Err(ErrorObject::invalid_request("already initialized")),
+            (_, Phase::Uninitialized) => Err(ErrorObject::new(
+                error_codes::SERVER_NOT_INITIALIZED,
+                "server not initialized",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Answer `initialize` and move to [`Phase::Initializing`].
+    pub fn initialize(
+        &mut self,
+        params: InitializeParams,
+        info: &ServerInfo,
+        capabilities: ServerCapabilities,
+    ) -> InitializeResult {
+        let version = negotiate_version(&params.protocol_version);
+        self.phase = Phase::Initializing;
+        self.protocol_version = Some(version);
+        self.client_info = Some(params.client_info);
+        self.client_capabilities = params.capabilities;
+        self.server_capabilities = capabilities.clone();
+        InitializeResult {
+            protocol_version: version.to_string(),
+            capabilities,
+            server_info: Implementation {
+                name: info.name.clone(),
+                version: info.version.clone(),
+                title: None,
+            },
+            instructions: info.instructions.clone(),
+        }
+    }
+
+    /// Feed a client notification; `notifications/initialized` completes the handshake.
+    pub fn on_notification(&mut self, method: &str) {
+        if method == INITIALIZED_NOTIFICATION && self.phase == Phase::Initializing {
+            self.phase = Phase::Ready;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn params(version: &str) -> InitializeParams {
+        serde_json::from_value(json!({
+            "protocolVersion": version,
+            "capabilities": {},
+            "clientInfo": {"name": "test", "version": "0"}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn gates_requests_until_initialized() {
+        let mut lc = Lifecycle::new();
+        assert!(lc.check_request("ping").is_ok());
+        let err = lc.check_request("tools/list").unwrap_err();
+        assert_eq!(err.code, error_codes::SERVER_NOT_INITIALIZED);
+
+        let res = lc.initialize(params("2025-03-26"), &ServerInfo::default(), Default::default());
+        assert_eq!(res.protocol_version, "2025-03-26");
+        assert_eq!(lc.phase(), Phase::Initializing);
+        assert!(lc.check_request("tools/list").is_ok());
+        assert!(lc.check_request("initialize").is_err());
+
+        lc.on_notification("notifications/initialized");
+        assert_eq!(lc.phase(), Phase::Ready);
+    }
+
+    #[test]
+    fn unknown_version_falls_back_to_latest() {
+        assert_eq!(negotiate_version("1999-01-01"), REQUIRED_PROTOCOL_VERSION);
+    }
+}
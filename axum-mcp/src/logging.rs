@@ -0,0 +1,247 @@
+//! MCP logging: `logging/setLevel` and `notifications/message`.
+//!
+//! With the `trace` feature, [`McpLogLayer`] also forwards `tracing` events
+//! emitted inside a tool call's span to the calling client.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::jsonrpc::{self, ErrorObject};
+
+pub const LOG_MESSAGE: &str = "notifications/message";
+
+/// Syslog severities (RFC 5424), least severe first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoggingLevel {
+    Debug,
+    #[default]
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+const LEVELS: [LoggingLevel; 8] = [
+    LoggingLevel::Debug,
+    LoggingLevel::Info,
+    LoggingLevel::Notice,
+    LoggingLevel::Warning,
+    LoggingLevel::Error,
+    LoggingLevel::Critical,
+    LoggingLevel::Alert,
+    LoggingLevel::Emergency,
+];
+
+/// The least severe level a client wants to receive. Shared by the transport,
+/// which updates it on `logging/setLevel`, and the client's tool calls.
+/// Until the client picks one, `info` and above are sent.
+#[derive(Clone, Debug)]
+pub struct LogLevel(Arc<AtomicU8>);
+
+impl Default for LogLevel {
+    fn default() -> Self {
+        Self::new(LoggingLevel::default())
+    }
+}
+
+impl LogLevel {
+    pub fn new(level: LoggingLevel) -> Self {
+        Self(Arc::new(AtomicU8::new(level as u8)))
+    }
+
+    pub fn get(&self) -> LoggingLevel {
+        LEVELS[self.0.load(Ordering::Relaxed) as usize]
+    }
+
+    pub fn set(&self, level: LoggingLevel) {
+        self.0.store(level as u8, Ordering::Relaxed);
+    }
+
+    pub fn enabled(&self, level: LoggingLevel) -> bool {
+        level >= self.get()
+    }
+}
+
+pub(crate) fn message_params(level: LoggingLevel, logger: Option<&str>, data: Value) -> Value {
+    let mut params = json!({"level": level, "data": data});
+    if let Some(logger) = logger {
+        params["logger"] = json!(logger);
+    }
+    params
+}
+
+#[derive(Deserialize)]
+struct SetLevelParams {
+    level: LoggingLevel,
+}
+
+/// Serve `logging/setLevel` for the client owning `log_level`.
+pub(crate) fn set_level(log_level: &LogLevel, params: Option<Value>) -> Result<Value, ErrorObject> {
+    let SetLevelParams { level } = jsonrpc::parse_params(params)?;
+    log_level.set(level);
+    Ok(json!({}))
+}
+
+#[cfg(feature = "trace")]
+pub use bridge::McpLogLayer;
+#[cfg(feature = "trace")]
+pub(crate) use bridge::CallSpan;
+
+#[cfg(feature = "trace")]
+mod bridge {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{LazyLock, Mutex};
+
+    use serde_json::{Map, Value, json};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id};
+    use tracing::{Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer};
+    use tracing_subscriber::registry::LookupSpan;
+
+    use super::LoggingLevel;
+    use crate::tool::ToolCtx;
+
+    const SINK_FIELD: &str = "mcp.sink";
+
+    /// Contexts of running tool calls, by the key their span carries.
+    static SINKS: LazyLock<Mutex<HashMap<u64, ToolCtx>>> = LazyLock::new(Default::default);
+    static NEXT_SINK: AtomicU64 = AtomicU64::new(0);
+
+    /// The span a tool call runs in; events inside it reach the caller.
+    pub(crate) struct CallSpan {
+        span: tracing::Span,
+        key: u64,
+    }
+
+    impl CallSpan {
+        pub(crate) fn new(tool: &str, ctx: &ToolCtx) -> Self {
+            let key = NEXT_SINK.fetch_add(1, Ordering::Relaxed);
+            let span = tracing::info_span!("mcp.tool_call", tool, mcp.sink = key);
+            if !span.is_disabled() {
+                SINKS.lock().unwrap().insert(key, ctx.clone());
+            }
+            Self { span, key }
+        }
+
+        pub(crate) fn span(&self) -> &tracing::Span {
+            &self.span
+        }
+    }
+
+    impl Drop for CallSpan {
+        fn drop(&mut self) {
+            SINKS.lock().unwrap().remove(&self.key);
+        }
+    }
+
+    /// A `tracing_subscriber` layer sending events from inside tool calls to
+    /// the calling client as `notifications/message`, with the event's target
+    /// as the logger and its fields as the data.
+    ///
+    /// ```ignore
+    /// tracing_subscriber::registry().with(axum_mcp::logging::McpLogLayer).init();
+    /// ```
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct McpLogLayer;
+
+    struct SinkKey(u64);
+
+    impl<S> Layer<S> for McpLogLayer
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            let mut key = SinkVisitor(None);
+            attrs.record(&mut key);
+            if let (Some(key), Some(span)) = (key.0, ctx.span(id)) {
+                span.extensions_mut().insert(SinkKey(key));
+            }
+        }
+
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+            let Some(scope) = ctx.event_scope(event) else {
+                return;
+            };
+            let Some(key) = scope.into_iter().find_map(|s| s.extensions().get::<SinkKey>().map(|k| k.0))
+            else {
+                return;
+            };
+            let Some(tool_ctx) = SINKS.lock().unwrap().get(&key).cloned() else {
+                return;
+            };
+            let level = match *event.metadata().level() {
+                Level::ERROR => LoggingLevel::Error,
+                Level::WARN => LoggingLevel::Warning,
+                Level::INFO => LoggingLevel::Info,
+                Level::DEBUG | Level::TRACE => LoggingLevel::Debug,
+            };
+            let mut fields = JsonVisitor(Map::new());
+            event.record(&mut fields);
+            tool_ctx.log_message(level, Some(event.metadata().target()), Value::Object(fields.0));
+        }
+    }
+
+    struct SinkVisitor(Option<u64>);
+
+    impl Visit for SinkVisitor {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            if field.name() == SINK_FIELD {
+                self.0 = Some(value);
+            }
+        }
+
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    struct JsonVisitor(Map<String, Value>);
+
+    impl Visit for JsonVisitor {
+        fn record_i64(&mut self, field: &Field, value: i64) {
+            self.0.insert(field.name().into(), json!(value));
+        }
+
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            self.0.insert(field.name().into(), json!(value));
+        }
+
+        fn record_f64(&mut self, field: &Field, value: f64) {
+            self.0.insert(field.name().into(), json!(value));
+        }
+
+        fn record_bool(&mut self, field: &Field, value: bool) {
+            self.0.insert(field.name().into(), json!(value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().into(), json!(value));
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().into(), json!(format!("{value:?}")));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_filter_by_severity() {
+        let level = LogLevel::default();
+        assert!(level.enabled(LoggingLevel::Info) && !level.enabled(LoggingLevel::Debug));
+        set_level(&level, Some(json!({"level": "error"}))).unwrap();
+        assert_eq!(level.get(), LoggingLevel::Error);
+        assert!(level.clone().enabled(LoggingLevel::Emergency) && !level.enabled(LoggingLevel::Warning));
+        assert!(set_level(&level, Some(json!({"level": "loud"}))).is_err());
+    }
+}
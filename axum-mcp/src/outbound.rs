@@ -0,0 +1,167 @@
+//! Server-to-client messages: a per-session outbox with event ids and a replay buffer.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+pub const DEFAULT_EVENT_BUFFER: usize = 256;
+
+/// One serialized JSON-RPC message and the id it was published under.
+#[derive(Clone, Debug)]
+pub struct OutboundEvent {
+    pub id: u64,
+    pub data: Arc<str>,
+}
+
+struct State {
+    next_id: u64,
+    /// Highest id handed to a live stream; fresh streams resume after it.
+    delivered: u64,
+    buffer: VecDeque<OutboundEvent>,
+    /// The one stream new events go to; the newest subscriber takes it over.
+    live: Option<mpsc::UnboundedSender<OutboundEvent>>,
+    closed: bool,
+    last_push: Instant,
+}
+
+/// Queue of messages the server wants to push to one client.
+///
+/// Every message gets a monotonically increasing id and is sent on exactly
+/// one stream, the most recently opened. The last `capacity` messages are
+/// retained so a reconnecting stream can replay what it missed.
+pub struct Outbox {
+    state: Mutex<State>,
+    capacity: usize,
+}
+
+impl Outbox {
+    pub fn new(capacity: usize) -> Self {
+        let state = State {
+            next_id: 1,
+            delivered: 0,
+            buffer: VecDeque::new(),
+            live: None,
+            closed: false,
+            last_push: Instant::now(),
+        };
+        Self { state: Mutex::new(state), capacity: capacity.max(1) }
+    }
+
+    /// Publish a message; returns its event id.
+    pub fn push<T: Serialize>(&self, message: &T) -> u64 {
+        let data: Arc<str> = serde_json::to_string(message).unwrap().into();
+        let mut state = self.state.lock().unwrap();
+        let event = OutboundEvent { id: state.next_id, data };
+        state.next_id += 1;
+        state.last_push = Instant::now();
+        if state.buffer.len() == self.capacity {
+            state.buffer.pop_front();
+        }
+        state.buffer.push_back(event.clone());
+        // Publishing under the lock keeps replay and live delivery in order.
+        if let Some(live) = &state.live
+            && live.send(event.clone()).is_err()
+        {
+            state.live = None;
+        }
+        event.id
+    }
+
+    /// Start a stream: buffered events to replay plus a receiver for new ones.
+    /// The stream open before stops getting new events and ends once drained.
+    ///
+    /// With `last_event_id` the replay starts right after it (reconnect);
+    /// without, it starts after the last event any stream delivered.
+    pub fn subscribe(
+        &self,
+        last_event_id: Option<u64>,
+    ) -> (Vec<OutboundEvent>, mpsc::UnboundedReceiver<OutboundEvent>) {
+        let mut state = self.state.lock().unwrap();
+        let after = last_event_id.unwrap_or(state.delivered);
+        let replay = state.buffer.iter().filter(|e| e.id > after).cloned().collect();
+        let (tx, rx) = mpsc::unbounded_channel();
+        // Closed: the sender is dropped, so the receiver ends right away.
+        if !state.closed {
+            state.live = Some(tx);
+        }
+        (replay, rx)
+    }
+
+    /// Whether a stream is open to take new events.
+    pub fn has_live_stream(&self) -> bool {
+        self.state.lock().unwrap().live.as_ref().is_some_and(|live| !live.is_closed())
+    }
+
+    /// When the last message was published.
+    pub fn last_push(&self) -> Instant {
+        self.state.lock().unwrap().last_push
+    }
+
+    /// Stop live delivery; the open stream ends after draining.
+    pub fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        state.live = None;
+    }
+
+    /// Record that `id` reached a client.
+    pub fn mark_delivered(&self, id: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.delivered = state.delivered.max(id);
+    }
+}
+
+impl Default for Outbox {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_BUFFER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn replays_after_last_event_id_within_capacity() {
+        let outbox = Outbox::new(2);
+        for n in 0..3 {
+            outbox.push(&json!({"n": n}));
+        }
+        let (replay, _) = outbox.subscribe(Some(0));
+        assert_eq!(replay.iter().map(|e| e.id).collect::<Vec<_>>(), vec![2, 3]);
+        let (replay, _) = outbox.subscribe(Some(2));
+        assert_eq!(replay.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn fresh_stream_skips_delivered_events() {
+        let outbox = Outbox::default();
+        outbox.push(&json!(1));
+        let id = outbox.push(&json!(2));
+        outbox.mark_delivered(id);
+        outbox.push(&json!(3));
+        let (replay, _) = outbox.subscribe(None);
+        assert_eq!(replay.len(), 1);
+        assert_eq!(&*replay[0].data, "3");
+    }
+
+    #[test]
+    fn each_event_goes_to_one_stream() {
+        let outbox = Outbox::default();
+        let (_, mut old) = outbox.subscribe(None);
+        outbox.push(&json!(1));
+        let (_, mut new) = outbox.subscribe(None);
+        outbox.push(&json!(2));
+        assert_eq!(&*old.try_recv().unwrap().data, "1");
+        assert!(old.try_recv().is_err());
+        assert_eq!(&*new.try_recv().unwrap().data, "2");
+        assert!(outbox.has_live_stream());
+        drop(new);
+        assert!(!outbox.has_live_stream());
+    }
+}
@@ -0,0 +1,104 @@
+//! Opaque cursors for the MCP `*/list` methods.
+//!
+//! Lists are ordered by a unique key (a tool name, a URI, ...) and a cursor
+//! encodes the last key handed out. The next page starts after that key, so a
+//! cursor stays meaningful while items are added or removed in between.
+
+use serde::Deserialize;
+
+use crate::jsonrpc::ErrorObject;
+
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Params shared by the paginated list requests.
+#[derive(Debug, Default, Deserialize)]
+pub struct ListParams {
+    pub cursor: Option<String>,
+}
+
+/// One page of a list plus the cursor of the next, if there is one.
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid cursor")]
+pub struct InvalidCursor;
+
+impl From<InvalidCursor> for ErrorObject {
+    fn from(e: InvalidCursor) -> Self {
+        ErrorObject::invalid_params(e)
+    }
+}
+
+const CURSOR_PREFIX: &str = "c1.";
+
+pub fn encode_cursor(last_key: &str) -> String {
+    let hex: String = last_key.bytes().map(|b| format!("{b:02x}")).collect();
+    format!("{CURSOR_PREFIX}{hex}")
+}
+
+pub fn decode_cursor(cursor: &str) -> Result<String, InvalidCursor> {
+    let hex = cursor.strip_prefix(CURSOR_PREFIX).ok_or(InvalidCursor)?;
+    if hex.len() % 2 != 0 {
+        return Err(InvalidCursor);
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or(InvalidCursor)?;
+    String::from_utf8(bytes).map_err(|_| InvalidCursor)
+}
+
+/// Cut one page out of `items`, which must be sorted by `key`.
+///
+/// `page_size == 0` returns everything after the cursor in one page.
+pub fn paginate<T>(
+    items: Vec<T>,
+    key: impl Fn(&T) -> &str,
+    cursor: Option<&str>,
+    page_size: usize,
+) -> Result<Page<T>, InvalidCursor> {
+    let after = cursor.map(decode_cursor).transpose()?;
+    let mut items: Vec<T> = match &after {
+        Some(after) => items.into_iter().filter(|t| key(t) > after.as_str()).collect(),
+        None => items,
+    };
+    let next_cursor = if page_size > 0 && items.len() > page_size {
+        items.truncate(page_size);
+        items.last().map(|t| encode_cursor(key(t)))
+    } else {
+        None
+    };
+    Ok(Page { items, next_cursor })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_roundtrip_and_garbage() {
+        let c = encode_cursor("tool/ü");
+        assert_eq!(decode_cursor(&c).unwrap(), "tool/ü");
+        for bad in ["", "abc", "c1.0", "c1.zz", "c1.ff"] {
+            assert!(decode_cursor(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn pages_survive_removal_of_the_last_key() {
+        let names = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let page = paginate(names(&["a", "b", "c", "d"]), |s| s, None, 2).unwrap();
+        assert_eq!(page.items, ["a", "b"]);
+        let cursor = page.next_cursor.unwrap();
+
+        // "b" disappears between calls; the cursor still resumes after it.
+        let page = paginate(names(&["a", "c", "d"]), |s| s, Some(&cursor), 2).unwrap();
+        assert_eq!(page.items, ["c", "d"]);
+        assert!(page.next_cursor.is_none());
+    }
+}
@@ -0,0 +1,176 @@
+//! Requests the server sends to the client, such as `sampling/createMessage`,
+//! and the routing of the client's responses back to whoever is waiting.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{Value, json};
+use tokio::sync::oneshot;
+
+use crate::cancel::CANCELLED_NOTIFICATION;
+use crate::jsonrpc::{ErrorObject, JSONRPC_VERSION, Request, RequestId, Response, ResponsePayload};
+use crate::tool::{ToolCtx, ToolError};
+
+/// How long a server-initiated request waits for the client by default.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+type Reply = Result<Value, ErrorObject>;
+
+#[derive(thiserror::Error, Debug)]
+pub enum PeerError {
+    #[error("client did not advertise the '{0}' capability")]
+    Unsupported(&'static str),
+    #[error("no channel to send requests to the client")]
+    NoChannel,
+    #[error("client did not answer within {0:?}")]
+    Timeout(Duration),
+    #[error("client answered with error {}: {}", .0.code, .0.message)]
+    Client(ErrorObject),
+    #[error("connection to the client closed")]
+    Closed,
+    #[error("invalid response from the client: {0}")]
+    InvalidResponse(String),
+}
+
+impl From<PeerError> for ToolError {
+    fn from(e: PeerError) -> Self {
+        ToolError::Internal(e.to_string())
+    }
+}
+
+/// Server-initiated requests still waiting for the client's response; one
+/// per client (an HTTP session, or the stdio connection).
+pub struct PendingRequests {
+    next_id: AtomicI64,
+    /// `None` once closed: new requests fail at once.
+    waiting: Mutex<Option<HashMap<RequestId, oneshot::Sender<Reply>>>>,
+}
+
+impl Default for PendingRequests {
+    fn default() -> Self {
+        Self { next_id: Default::default(), waiting: Mutex::new(Some(HashMap::new())) }
+    }
+}
+
+impl PendingRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hand a client response to the request waiting for it. Returns `false`
+    /// for responses nobody waits for (unknown, timed out or cancelled).
+    pub fn resolve(&self, response: Response) -> bool {
+        let Some(id) = response.id else {
+            return false;
+        };
+        let Some(tx) = self.waiting.lock().unwrap().as_mut().and_then(|w| w.remove(&id)) else {
+            return false;
+        };
+        let reply = match response.payload {
+            ResponsePayload::Result(v) => Ok(v),
+            ResponsePayload::Error(e) => Err(e),
+        };
+        tx.send(reply).is_ok()
+    }
+
+    /// Fail every waiting request and any made later with
+    /// [`PeerError::Closed`]; called when the client goes away.
+    pub fn close(&self) {
+        self.waiting.lock().unwrap().take();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.waiting.lock().unwrap().is_none()
+    }
+
+    pub fn len(&self) -> usize {
+        self.waiting.lock().unwrap().as_ref().map_or(0, HashMap::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn start(self: &Arc<Self>) -> Result<Outstanding, PeerError> {
+        let id = RequestId::Number(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let (tx, rx) = oneshot::channel();
+        let mut waiting = self.waiting.lock().unwrap();
+        waiting.as_mut().ok_or(PeerError::Closed)?.insert(id.clone(), tx);
+        Ok(Outstanding { id, rx, pending: self.clone() })
+    }
+}
+
+/// Deregisters the request however waiting for it ends.
+struct Outstanding {
+    id: RequestId,
+    rx: oneshot::Receiver<Reply>,
+    pending: Arc<PendingRequests>,
+}
+
+impl Drop for Outstanding {
+    fn drop(&mut self) {
+        if let Some(waiting) = self.pending.waiting.lock().unwrap().as_mut() {
+            waiting.remove(&self.id);
+        }
+    }
+}
+
+/// Send `method` to the client behind `ctx` and wait for its result.
+pub(crate) async fn request(
+    ctx: &ToolCtx,
+    pending: &Arc<PendingRequests>,
+    method: &str,
+    params: Value,
+    timeout: Duration,
+) -> Result<Value, PeerError> {
+    let mut outstanding = pending.start()?;
+    let req = Request {
+        jsonrpc: JSONRPC_VERSION.to_string(),
+        id: outstanding.id.clone(),
+        method: method.to_string(),
+        params: Some(params),
+    };
+    ctx.send(serde_json::to_value(req).unwrap());
+    match tokio::time::timeout(timeout, &mut outstanding.rx).await {
+        Ok(Ok(reply)) => reply.map_err(PeerError::Client),
+        Ok(Err(_)) => Err(PeerError::Closed),
+        Err(_) => {
+            let params = json!({"requestId": outstanding.id, "reason": "timed out"});
+            ctx.notify(CANCELLED_NOTIFICATION, Some(params));
+            Err(PeerError::Timeout(timeout))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn responses_reach_their_request() {
+        let pending = Arc::new(PendingRequests::new());
+        let (mut a, mut b) = (pending.start().unwrap(), pending.start().unwrap());
+        assert_ne!(a.id, b.id);
+
+        assert!(pending.resolve(Response::result(b.id.clone(), json!({"n": 2}))));
+        assert_eq!((&mut b.rx).await.unwrap().unwrap(), json!({"n": 2}));
+        // Unknown or repeated ids are ignored.
+        assert!(!pending.resolve(Response::result(RequestId::Number(99), json!({}))));
+
+        pending.close();
+        assert!((&mut a.rx).await.is_err());
+        assert!(matches!(pending.start(), Err(PeerError::Closed)));
+    }
+
+    #[test]
+    fn abandoned_requests_are_forgotten() {
+        let pending = Arc::new(PendingRequests::new());
+        let outstanding = pending.start().unwrap();
+        let id = outstanding.id.clone();
+        drop(outstanding);
+        assert!(pending.is_empty());
+        assert!(!pending.resolve(Response::result(id, json!({}))));
+    }
+}
@@ -1,3 +1,18 @@
-pub use crate::tool::{ToolCtx, ToolDescriptor, ToolError, ToolHandler};
+pub use crate::tool::{
+    CallToolResult, IntoToolResult, Tool, ToolAnnotations, ToolCtx, ToolDescriptor, ToolError, ToolHandler,
+};
 pub use crate::registry::{ToolRegistry, TOOLS, ToolRegistration};
-pub use crate::{IntoJsonValue, McpLayer, McpLayerConfig};
+pub use crate::resource::{
+    IntoResourceContents, ResourceContents, ResourceDescriptor, ResourceError, ResourceHandler,
+    ResourceNotifier, ResourceRegistry, RESOURCES, ResourceRegistration, UriVars,
+};
+pub use crate::prompt::{
+    IntoPromptMessages, PromptArgument, PromptDescriptor, PromptError, PromptHandler, PromptMessage,
+    PromptRegistry, PROMPTS, PromptRegistration,
+};
+pub use crate::content::{ContentBlock, Role};
+pub use crate::logging::LoggingLevel;
+pub use crate::sampling::{CreateMessageRequest, CreateMessageResult, SamplingMessage};
+pub use crate::elicitation::Elicitation;
+pub use crate::roots::Root;
+pub use crate::{McpLayer, McpLayerConfig};
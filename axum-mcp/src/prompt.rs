@@ -0,0 +1,341 @@
+//! MCP prompts: named message templates a client can list and fill in.
+
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use linkme::distributed_slice;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use tokio::sync::RwLock;
+
+use crate::completion::{Choices, CompletionProvider, DeclaredProviders, schema_choices};
+use crate::content::{ContentBlock, Role};
+use crate::jsonrpc::{self, ErrorObject};
+use crate::lifecycle::PromptsCapability;
+use crate::pagination::{InvalidCursor, ListParams, Page, paginate};
+use crate::schema::RootSchema;
+use crate::tool::ToolCtx;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PromptMessage {
+    pub role: Role,
+    pub content: ContentBlock,
+}
+
+impl PromptMessage {
+    pub fn user(content: impl Into<ContentBlock>) -> Self {
+        Self { role: Role::User, content: content.into() }
+    }
+
+    pub fn assistant(content: impl Into<ContentBlock>) -> Self {
+        Self { role: Role::Assistant, content: content.into() }
+    }
+}
+
+/// What a prompt function may return.
+pub trait IntoPromptMessages {
+    fn into_prompt_messages(self) -> Result<Vec<PromptMessage>, PromptError>;
+}
+
+impl IntoPromptMessages for Vec<PromptMessage> {
+    fn into_prompt_messages(self) -> Result<Vec<PromptMessage>, PromptError> {
+        Ok(self)
+    }
+}
+
+impl IntoPromptMessages for PromptMessage {
+    fn into_prompt_messages(self) -> Result<Vec<PromptMessage>, PromptError> {
+        Ok(vec![self])
+    }
+}
+
+/// A single user message.
+impl IntoPromptMessages for String {
+    fn into_prompt_messages(self) -> Result<Vec<PromptMessage>, PromptError> {
+        Ok(vec![PromptMessage::user(self)])
+    }
+}
+
+impl<T: IntoPromptMessages, E: std::fmt::Display> IntoPromptMessages for Result<T, E> {
+    fn into_prompt_messages(self) -> Result<Vec<PromptMessage>, PromptError> {
+        self.map_err(|e| PromptError::Internal(e.to_string()))?.into_prompt_messages()
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum PromptError {
+    #[error("prompt_not_found: {0}")]
+    NotFound(String),
+    #[error("invalid_args: {0}")]
+    InvalidArgs(String),
+    #[error("internal: {0}")]
+    Internal(String),
+}
+
+impl From<PromptError> for ErrorObject {
+    fn from(e: PromptError) -> Self {
+        match e {
+            PromptError::NotFound(_) | PromptError::InvalidArgs(_) => ErrorObject::invalid_params(e),
+            PromptError::Internal(_) => ErrorObject::internal(e),
+        }
+    }
+}
+
+#[async_trait]
+pub trait PromptHandler: Send + Sync {
+    /// Render the prompt; `args` is the JSON object of the client's string arguments.
+    async fn get(&self, ctx: &ToolCtx, args: Value) -> Result<Vec<PromptMessage>, PromptError>;
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub required: bool,
+}
+
+/// The arguments of a prompt, one per property of its `schemars` struct.
+pub fn arguments_from_schema(schema: &RootSchema) -> Vec<PromptArgument> {
+    let value = schema.as_value();
+    let required: Vec<&str> = value["required"]
+        .as_array()
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let Some(properties) = value["properties"].as_object() else {
+        return Vec::new();
+    };
+    properties
+        .iter()
+        .map(|(name, prop)| PromptArgument {
+            name: name.clone(),
+            description: prop["description"].as_str().map(str::to_string),
+            required: required.contains(&name.as_str()),
+        })
+        .collect()
+}
+
+#[derive(Clone)]
+pub struct PromptDescriptor {
+    pub name: String,
+    pub description: Option<String>,
+    pub arguments: Vec<PromptArgument>,
+    pub handler: Arc<dyn PromptHandler + Send + Sync>,
+    /// Completion providers by argument name.
+    pub completions: HashMap<String, Arc<dyn CompletionProvider>>,
+}
+
+impl PromptDescriptor {
+    pub fn with_completion(mut self, argument: &str, provider: Arc<dyn CompletionProvider>) -> Self {
+        self.completions.insert(argument.to_string(), provider);
+        self
+    }
+}
+
+pub struct PromptRegistration {
+    pub name: &'static str,
+    pub description: Option<&'static str>,
+    /// `None` for prompts without arguments.
+    pub arguments_schema: Option<fn() -> RootSchema>,
+    pub build_handler: fn() -> Arc<dyn PromptHandler + Send + Sync>,
+    /// From `complete(arg = provider_fn)`; enum arguments complete on their own.
+    pub completions: fn() -> DeclaredProviders,
+    pub defined_at_file: &'static str,
+    pub defined_at_line: u32,
+}
+
+#[distributed_slice]
+pub static PROMPTS: [PromptRegistration] = [..];
+
+pub struct PromptRegistry {
+    inner: RwLock<BTreeMap<String, PromptDescriptor>>,
+    app_state: Arc<dyn Any + Send + Sync>,
+}
+
+impl PromptRegistry {
+    pub fn empty_with_state(app_state: Arc<dyn Any + Send + Sync>) -> Arc<Self> {
+        Arc::new(Self { inner: Default::default(), app_state })
+    }
+
+    pub fn gather_with_state(app_state: Arc<dyn Any + Send + Sync>) -> Arc<Self> {
+        let reg = Self::empty_with_state(app_state);
+        for item in PROMPTS {
+            let schema = item.arguments_schema.map(|s| s());
+            let arguments = schema.as_ref().map(arguments_from_schema).unwrap_or_default();
+            let mut completions: HashMap<String, Arc<dyn CompletionProvider>> = HashMap::new();
+            for (arg, values) in schema.as_ref().map(schema_choices).unwrap_or_default() {
+                completions.insert(arg, Arc::new(Choices(values)));
+            }
+            for (arg, provider) in (item.completions)() {
+                if !arguments.iter().any(|a| a.name == arg) {
+                    panic!(
+                        "MCP prompt '{}' has no argument '{arg}' to complete\nat {}:{}",
+                        item.name, item.defined_at_file, item.defined_at_line
+                    );
+                }
+                completions.insert(arg.to_string(), provider);
+            }
+            let desc = PromptDescriptor {
+                name: item.name.to_string(),
+                description: item.description.map(str::to_string),
+                arguments,
+                handler: (item.build_handler)(),
+                completions,
+            };
+            if let Some(prev) = futures::executor::block_on(reg.insert(desc)) {
+                panic!(
+                    "duplicate MCP prompt name '{}'\nfirst defined previously, now again at {}:{}",
+                    prev.name, item.defined_at_file, item.defined_at_line
+                );
+            }
+        }
+        reg
+    }
+
+    pub async fn insert(&self, desc: PromptDescriptor) -> Option<PromptDescriptor> {
+        self.inner.write().await.insert(desc.name.clone(), desc)
+    }
+
+    pub async fn remove(&self, name: &str) -> Option<PromptDescriptor> {
+        self.inner.write().await.remove(name)
+    }
+
+    pub async fn get(&self, name: &str) -> Option<PromptDescriptor> {
+        self.inner.read().await.get(name).cloned()
+    }
+
+    /// One page of prompts sorted by name, as served by `prompts/list`.
+    pub async fn list_page(
+        &self,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<Page<PromptDescriptor>, InvalidCursor> {
+        let all = self.inner.read().await.values().cloned().collect();
+        paginate(all, |d| &d.name, cursor, page_size)
+    }
+
+    /// Render prompt `name`. Required arguments are checked here so handlers
+    /// only see complete argument sets.
+    pub async fn render(
+        &self,
+        name: &str,
+        args: Map<String, Value>,
+        ctx: ToolCtx,
+    ) -> Result<Vec<PromptMessage>, PromptError> {
+        let d = self.get(name).await.ok_or_else(|| PromptError::NotFound(name.to_string()))?;
+        if let Some(missing) = d.arguments.iter().find(|a| a.required && !args.contains_key(&a.name)) {
+            return Err(PromptError::InvalidArgs(format!("missing argument '{}'", missing.name)));
+        }
+        d.handler.get(&ctx, Value::Object(args)).await
+    }
+
+    /// The `prompts` capability advertised during `initialize`.
+    pub fn capability(&self) -> PromptsCapability {
+        PromptsCapability::default()
+    }
+
+    pub fn app_state(&self) -> Arc<dyn Any + Send + Sync> {
+        self.app_state.clone()
+    }
+}
+
+#[derive(Serialize)]
+struct PromptMeta<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    arguments: &'a [PromptArgument],
+}
+
+#[derive(Deserialize)]
+struct GetParams {
+    name: String,
+    #[serde(default)]
+    arguments: Map<String, Value>,
+}
+
+/// Serve a `prompts/*` request; shared by the transports.
+pub(crate) async fn dispatch(
+    prompts: &PromptRegistry,
+    method: &str,
+    params: Option<Value>,
+    ctx: ToolCtx,
+    page_size: usize,
+) -> Result<Value, ErrorObject> {
+    match method {
+        "prompts/list" => {
+            let ListParams { cursor } = jsonrpc::parse_params(params)?;
+            let page = prompts.list_page(cursor.as_deref(), page_size).await?;
+            let items: Vec<_> = page
+                .items
+                .iter()
+                .map(|d| PromptMeta {
+                    name: &d.name,
+                    description: d.description.as_deref(),
+                    arguments: &d.arguments,
+                })
+                .collect();
+            let mut result = json!({"prompts": items});
+            if let Some(next) = page.next_cursor {
+                result["nextCursor"] = json!(next);
+            }
+            Ok(result)
+        }
+        "prompts/get" => {
+            let GetParams { name, arguments } = jsonrpc::parse_params(params)?;
+            let description = prompts.get(&name).await.and_then(|d| d.description);
+            let messages = prompts.render(&name, arguments, ctx).await?;
+            let mut result = json!({"messages": messages});
+            if let Some(description) = description {
+                result["description"] = json!(description);
+            }
+            Ok(result)
+        }
+        other => Err(ErrorObject::method_not_found(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Arguments to a code review prompt.
+    #[derive(schemars::JsonSchema)]
+    #[allow(dead_code)]
+    struct ReviewArgs {
+        /// The code to review.
+        code: String,
+        focus: Option<String>,
+    }
+
+    #[test]
+    fn arguments_follow_the_schema() {
+        let args = arguments_from_schema(&schemars::schema_for!(ReviewArgs));
+        assert_eq!(
+            args,
+            [
+                PromptArgument {
+                    name: "code".into(),
+                    description: Some("The code to review.".into()),
+                    required: true,
+                },
+                PromptArgument { name: "focus".into(), description: None, required: false },
+            ]
+        );
+    }
+
+    #[test]
+    fn messages_serialize_with_roles_and_blocks() {
+        let msgs = [PromptMessage::user("hi"), PromptMessage::assistant(ContentBlock::image(b"\x89P", "image/png"))];
+        assert_eq!(
+            serde_json::to_value(msgs).unwrap(),
+            json!([
+                {"role":"user","content":{"type":"text","text":"hi"}},
+                {"role":"assistant","content":{"type":"image","data":"iVA=","mimeType":"image/png"}}
+            ])
+        );
+    }
+}
@@ -1,11 +1,15 @@
 use std::{any::Any, collections::HashMap, sync::Arc};
 
 use linkme::distributed_slice;
-use serde_json::Value;
+use serde_json::{Value, json};
 use crate::schema::RootSchema;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, broadcast};
 
-use crate::tool::{ToolCtx, ToolDescriptor, ToolError, ToolHandler};
+use crate::lifecycle::ToolsCapability;
+use crate::pagination::{InvalidCursor, Page, paginate};
+use crate::tool::{
+    CallToolResult, Tool, ToolAnnotations, ToolCtx, ToolDescriptor, ToolError, ToolHandler, wraps_output,
+};
 
 pub struct ToolRegistration {
     pub name: &'static str,
@@ -16,19 +20,25 @@ pub struct ToolRegistration {
     pub defined_at_file: &'static str,
     pub defined_at_line: u32,
     pub structured: bool,
+    pub title: Option<&'static str>,
+    pub annotations: ToolAnnotations,
 }
 
 #[distributed_slice]
 pub static TOOLS: [ToolRegistration] = [..];
 
+pub const TOOLS_LIST_CHANGED: &str = "notifications/tools/list_changed";
+
 pub struct ToolRegistry {
     inner: RwLock<HashMap<String, ToolDescriptor>>,
     app_state: Arc<dyn Any + Send + Sync>,
+    changes: broadcast::Sender<()>,
 }
 
 impl ToolRegistry {
     pub fn empty_with_state(app_state: Arc<dyn Any + Send + Sync>) -> Arc<Self> {
-        Arc::new(Self { inner: Default::default(), app_state })
+        let (changes, _) = broadcast::channel(16);
+        Arc::new(Self { inner: Default::default(), app_state, changes })
     }
 
     pub fn gather_with_state(app_state: Arc<dyn Any + Send + Sync>) -> Arc<Self> {
@@ -42,6 +52,8 @@ impl ToolRegistry {
                 output_schema: (item.output_schema)(),
                 handler: (item.build_handler)(),
                 structured: item.structured,
+                title: item.title,
+                annotations: item.annotations,
             };
             // Duplicate detection with helpful message
             let existed = futures::executor::block_on(reg.insert(desc));
@@ -56,23 +68,104 @@ impl ToolRegistry {
     }
 
     pub async fn insert(&self, desc: ToolDescriptor) -> Option<ToolDescriptor> {
-        self.inner.write().await.insert(desc.name.to_string(), desc)
+        let prev = self.inner.write().await.insert(desc.name.to_string(), desc);
+        self.changed();
+        prev
+    }
+
+    pub async fn remove(&self, name: &str) -> Option<ToolDescriptor> {
+        let prev = self.inner.write().await.remove(name);
+        if prev.is_some() {
+            self.changed();
+        }
+        prev
+    }
+
+    /// Swap the implementation of an existing tool; returns the old descriptor.
+    pub async fn replace(&self, desc: ToolDescriptor) -> Result<ToolDescriptor, ToolError> {
+        let mut inner = self.inner.write().await;
+        let Some(slot) = inner.get_mut(desc.name) else {
+            return Err(ToolError::NotFound(desc.name.to_string()));
+        };
+        let prev = std::mem::replace(slot, desc);
+        drop(inner);
+        self.changed();
+        Ok(prev)
+    }
+
+    /// Fires after every change to the set of tools. Bursts may be coalesced
+    /// (a lagging receiver sees `Lagged`), which still means "re-list".
+    pub fn subscribe(&self) -> broadcast::Receiver<()> {
+        self.changes.subscribe()
+    }
+
+    fn changed(&self) {
+        // No receivers just means nobody is connected yet.
+        let _ = self.changes.send(());
     }
 
     pub async fn get(&self, name: &str) -> Option<ToolDescriptor> {
         self.inner.read().await.get(name).cloned()
     }
 
-    pub async fn list(&self) -> Vec<(String, Option<&'static str>, RootSchema, RootSchema)> {
-        self.inner
-            .read()
-            .await
-            .values()
-            .map(|d| (d.name.to_string(), d.description, d.input_schema.clone(), d.output_schema.clone()))
-            .collect()
+    /// Every tool as `tools/list` shows it, sorted by name.
+    pub async fn list(&self) -> Vec<Tool> {
+        self.descriptors().await.iter().map(Tool::from).collect()
+    }
+
+    /// One page of tools sorted by name, as served by `tools/list`.
+    pub async fn list_page(
+        &self,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<Page<ToolDescriptor>, InvalidCursor> {
+        paginate(self.descriptors().await, |d| d.name, cursor, page_size)
     }
 
-    pub async fn call(&self, name: &str, args: Value) -> Result<Value, ToolError> {
+    /// Every tool descriptor, sorted by name.
+    pub async fn descriptors(&self) -> Vec<ToolDescriptor> {
+        let mut tools: Vec<_> = self.inner.read().await.values().cloned().collect();
+        tools.sort_unstable_by_key(|d| d.name);
+        tools
+    }
+
+    pub async fn call(&self, name: &str, args: Value) -> Result<CallToolResult, ToolError> {
+        self.call_with(name, args, ToolCtx::new(self.app_state.clone())).await
+    }
+
+    /// Like [`ToolRegistry::call`], with a caller-built context (e.g. carrying the session).
+    pub async fn call_with(&self, name: &str, args: Value, ctx: ToolCtx) -> Result<CallToolResult, ToolError> {
+        let (d, mut out) = self.invoke(name, args, ctx).await?;
+        // Unstructured tools answer with their text content alone.
+        let Some(value) = out.structured_content.take().filter(|_| d.structured) else {
+            return Ok(out);
+        };
+        #[cfg(feature = "jsonschema")]
+        if let Err(e) = crate::schema::validate_json(&value, &d.output_schema) {
+            return Err(ToolError::Internal(format!("output schema validation failed: {}", e)));
+        }
+        // `structuredContent` must be an object; match the advertised schema.
+        out.structured_content = Some(if wraps_output(&d.output_schema) || !value.is_object() {
+            json!({ "result": value })
+        } else {
+            value
+        });
+        Ok(out)
+    }
+
+    /// The handler's result as it returned it, `structured_content` included
+    /// whatever its shape; for the legacy `{"op": ...}` bodies.
+    pub(crate) async fn call_raw(&self, name: &str, args: Value) -> Result<CallToolResult, ToolError> {
+        let ctx = ToolCtx::new(self.app_state.clone());
+        Ok(self.invoke(name, args, ctx).await?.1)
+    }
+
+    async fn invoke(
+        &self,
+        name: &str,
+        args: Value,
+        ctx: ToolCtx,
+    ) -> Result<(ToolDescriptor, CallToolResult), ToolError> {
         let d = self
             .inner
             .read()
@@ -80,18 +173,28 @@ impl ToolRegistry {
             .get(name)
             .cloned()
             .ok_or_else(|| ToolError::NotFound(name.to_string()))?;
-        let ctx = ToolCtx { app_state: self.app_state.clone(), req_meta: Default::default() };
         // Optional runtime schema validation (feature = jsonschema)
         #[cfg(feature = "jsonschema")]
         if let Err(e) = crate::schema::validate_json(&args, &d.input_schema) {
             return Err(ToolError::InvalidArgs(e));
         }
-        let out = d.handler.call(&ctx, args).await?;
-        #[cfg(feature = "jsonschema")]
-        if let Err(e) = crate::schema::validate_json(&out, &d.output_schema) {
-            return Err(ToolError::Internal(format!("output schema validation failed: {}", e)));
-        }
-        Ok(out)
+        let call = d.handler.call(&ctx, args);
+        #[cfg(feature = "trace")]
+        let call_span = crate::logging::CallSpan::new(name, &ctx);
+        #[cfg(feature = "trace")]
+        let call = tracing::Instrument::instrument(call, call_span.span().clone());
+        // Checked first so a call cancelled before it started never runs.
+        let out = tokio::select! {
+            biased;
+            _ = ctx.cancellation().cancelled() => return Err(ToolError::Cancelled),
+            out = call => out?,
+        };
+        Ok((d, out))
+    }
+
+    /// The `tools` capability advertised during `initialize`.
+    pub fn capability(&self) -> ToolsCapability {
+        ToolsCapability { list_changed: true }
     }
 
     pub fn app_state(&self) -> Arc<dyn Any + Send + Sync> { self.app_state.clone() }
@@ -105,8 +208,8 @@ mod tests {
     struct Echo;
     #[async_trait]
     impl ToolHandler for Echo {
-        async fn call(&self, _ctx: &crate::tool::ToolCtx, args: Value) -> Result<Value, ToolError> {
-            Ok(args)
+        async fn call(&self, _ctx: &crate::tool::ToolCtx, args: Value) -> Result<CallToolResult, ToolError> {
+            Ok(args.into())
         }
     }
 
@@ -120,9 +223,39 @@ mod tests {
             output_schema: schemars::schema_for!(serde_json::Value),
             handler: Arc::new(Echo),
             structured: true,
+            title: None,
+            annotations: Default::default(),
         }).await;
 
         let out = r.call("echo", serde_json::json!({"a":1})).await.unwrap();
-        assert_eq!(out, serde_json::json!({"a":1}));
+        assert_eq!(out.structured_content, Some(serde_json::json!({"a":1})));
+    }
+
+    #[tokio::test]
+    async fn remove_and_replace_announce_changes() {
+        let r = ToolRegistry::empty_with_state(Arc::new(()));
+        let echo = ToolDescriptor {
+            name: "echo",
+            description: None,
+            input_schema: schemars::schema_for!(serde_json::Value),
+            output_schema: schemars::schema_for!(serde_json::Value),
+            handler: Arc::new(Echo),
+            structured: true,
+            title: None,
+            annotations: Default::default(),
+        };
+        assert!(matches!(r.replace(echo.clone()).await, Err(ToolError::NotFound(_))));
+
+        let mut changes = r.subscribe();
+        r.insert(echo.clone()).await;
+        let prev = r.replace(ToolDescriptor { description: Some("v2"), ..echo }).await.unwrap();
+        assert!(prev.description.is_none());
+        assert_eq!(r.get("echo").await.unwrap().description, Some("v2"));
+        assert!(r.remove("echo").await.is_some());
+        assert!(r.remove("echo").await.is_none());
+        for _ in 0..3 {
+            changes.try_recv().unwrap();
+        }
+        assert!(changes.try_recv().is_err());
     }
 }
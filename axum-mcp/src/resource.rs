@@ -0,0 +1,603 @@
+//! MCP resources: read-only context (files, rows, reports) addressed by URI.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::any::Any;
+
+use async_trait::async_trait;
+use base64::Engine;
+use linkme::distributed_slice;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::sync::{RwLock, broadcast};
+
+use crate::completion::{CompletionProvider, DeclaredProviders};
+use crate::jsonrpc::{self, ErrorObject, Notification, error_codes};
+use crate::lifecycle::ResourcesCapability;
+use crate::pagination::{InvalidCursor, ListParams, Page, paginate};
+use crate::tool::ToolCtx;
+use crate::uri_template::{InvalidTemplate, UriTemplate};
+
+pub const RESOURCE_UPDATED: &str = "notifications/resources/updated";
+
+/// Variables extracted from a templated resource URI.
+pub type UriVars = HashMap<String, String>;
+
+/// The body of a resource: UTF-8 text or base64-encoded bytes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(flatten)]
+    pub body: ResourceBody,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceBody {
+    Text(String),
+    /// Base64, as it goes on the wire.
+    Blob(String),
+}
+
+impl ResourceContents {
+    pub fn text(uri: impl Into<String>, text: impl Into<String>) -> Self {
+        Self { uri: uri.into(), mime_type: None, body: ResourceBody::Text(text.into()) }
+    }
+
+    pub fn blob(uri: impl Into<String>, bytes: impl AsRef<[u8]>) -> Self {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+        Self { uri: uri.into(), mime_type: None, body: ResourceBody::Blob(encoded) }
+    }
+
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+}
+
+/// What a resource handler may return; `mime_type` is the one declared for
+/// the resource, if any.
+pub trait IntoResourceContents {
+    fn into_resource_contents(
+        self,
+        uri: &str,
+        mime_type: Option<&str>,
+    ) -> Result<Vec<ResourceContents>, ResourceError>;
+}
+
+fn single(contents: ResourceContents, mime_type: Option<&str>) -> Vec<ResourceContents> {
+    match mime_type {
+        Some(m) => vec![contents.with_mime_type(m)],
+        None => vec![contents],
+    }
+}
+
+impl IntoResourceContents for String {
+    fn into_resource_contents(self, uri: &str, mime_type: Option<&str>) -> Result<Vec<ResourceContents>, ResourceError> {
+        Ok(single(ResourceContents::text(uri, self), mime_type))
+    }
+}
+
+impl IntoResourceContents for &'static str {
+    fn into_resource_contents(self, uri: &str, mime_type: Option<&str>) -> Result<Vec<ResourceContents>, ResourceError> {
+        self.to_string().into_resource_contents(uri, mime_type)
+    }
+}
+
+impl IntoResourceContents for Vec<u8> {
+    fn into_resource_contents(self, uri: &str, mime_type: Option<&str>) -> Result<Vec<ResourceContents>, ResourceError> {
+        Ok(single(ResourceContents::blob(uri, self), mime_type))
+    }
+}
+
+impl IntoResourceContents for axum::body::Bytes {
+    fn into_resource_contents(self, uri: &str, mime_type: Option<&str>) -> Result<Vec<ResourceContents>, ResourceError> {
+        Ok(single(ResourceContents::blob(uri, self), mime_type))
+    }
+}
+
+impl<T: Serialize> IntoResourceContents for axum::Json<T> {
+    fn into_resource_contents(self, uri: &str, mime_type: Option<&str>) -> Result<Vec<ResourceContents>, ResourceError> {
+        let text = serde_json::to_string(&self.0).map_err(|e| ResourceError::Internal(e.to_string()))?;
+        Ok(single(ResourceContents::text(uri, text), Some(mime_type.unwrap_or("application/json"))))
+    }
+}
+
+impl IntoResourceContents for ResourceContents {
+    fn into_resource_contents(self, _uri: &str, _mime_type: Option<&str>) -> Result<Vec<ResourceContents>, ResourceError> {
+        Ok(vec![self])
+    }
+}
+
+impl IntoResourceContents for Vec<ResourceContents> {
+    fn into_resource_contents(self, _uri: &str, _mime_type: Option<&str>) -> Result<Vec<ResourceContents>, ResourceError> {
+        Ok(self)
+    }
+}
+
+impl<T: IntoResourceContents, E: std::fmt::Display> IntoResourceContents for Result<T, E> {
+    fn into_resource_contents(self, uri: &str, mime_type: Option<&str>) -> Result<Vec<ResourceContents>, ResourceError> {
+        match self {
+            Ok(v) => v.into_resource_contents(uri, mime_type),
+            Err(e) => Err(ResourceError::Internal(e.to_string())),
+        }
+    }
+}
+
+/// Build the `T` of an `axum::extract::Path<T>` from matched template variables.
+///
+/// `T` is either a struct with one field per variable or, for single-variable
+/// templates, a scalar. Values that don't parse mean `uri` names no resource.
+pub fn path_from_vars<T: DeserializeOwned>(uri: &str, vars: &UriVars) -> Result<T, ResourceError> {
+    let not_found = || ResourceError::NotFound(uri.to_string());
+    let query = serde_urlencoded::to_string(vars).map_err(|_| not_found())?;
+    if let Ok(value) = serde_urlencoded::from_str::<T>(&query) {
+        return Ok(value);
+    }
+    let mut values = vars.values();
+    match (values.next(), values.next()) {
+        (Some(only), None) => {
+            let query = serde_urlencoded::to_string([("v", only)]).map_err(|_| not_found())?;
+            serde_urlencoded::from_str::<HashMap<String, T>>(&query)
+                .ok()
+                .and_then(|mut m| m.remove("v"))
+                .ok_or_else(not_found)
+        }
+        _ => Err(not_found()),
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ResourceError {
+    #[error("resource_not_found: {0}")]
+    NotFound(String),
+    #[error("internal: {0}")]
+    Internal(String),
+}
+
+impl From<ResourceError> for ErrorObject {
+    fn from(e: ResourceError) -> Self {
+        match &e {
+            ResourceError::NotFound(uri) => ErrorObject::new(
+                error_codes::RESOURCE_NOT_FOUND,
+                "Resource not found",
+            )
+            .with_data(json!({"uri": uri})),
+            ResourceError::Internal(_) => ErrorObject::internal(e),
+        }
+    }
+}
+
+#[async_trait]
+pub trait ResourceHandler: Send + Sync {
+    /// Produce the contents of `uri`; `vars` holds the template variables it matched.
+    async fn read(
+        &self,
+        ctx: &ToolCtx,
+        uri: &str,
+        vars: &UriVars,
+    ) -> Result<Vec<ResourceContents>, ResourceError>;
+}
+
+/// A resource, or a family of them when `uri` is a URI template.
+#[derive(Clone)]
+pub struct ResourceDescriptor {
+    pub uri: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub mime_type: Option<String>,
+    pub handler: Arc<dyn ResourceHandler + Send + Sync>,
+    /// Completion providers by template variable.
+    pub completions: HashMap<String, Arc<dyn CompletionProvider>>,
+}
+
+impl ResourceDescriptor {
+    pub fn with_completion(mut self, variable: &str, provider: Arc<dyn CompletionProvider>) -> Self {
+        self.completions.insert(variable.to_string(), provider);
+        self
+    }
+}
+
+pub struct ResourceRegistration {
+    pub uri: &'static str,
+    pub name: &'static str,
+    pub description: Option<&'static str>,
+    pub mime_type: Option<&'static str>,
+    pub build_handler: fn() -> Arc<dyn ResourceHandler + Send + Sync>,
+    /// From `complete(var = provider_fn)`.
+    pub completions: fn() -> DeclaredProviders,
+    pub defined_at_file: &'static str,
+    pub defined_at_line: u32,
+}
+
+#[distributed_slice]
+pub static RESOURCES: [ResourceRegistration] = [..];
+
+/// Tells subscribed clients that a resource changed. Cheap to clone, so the
+/// application can keep one in its state.
+#[derive(Clone)]
+pub struct ResourceNotifier {
+    updates: broadcast::Sender<String>,
+}
+
+impl ResourceNotifier {
+    /// Send `notifications/resources/updated` for `uri` to every client
+    /// subscribed to it.
+    pub fn updated(&self, uri: impl Into<String>) {
+        // No receivers just means no client is connected.
+        let _ = self.updates.send(uri.into());
+    }
+}
+
+/// The URIs one client subscribed to with `resources/subscribe`.
+#[derive(Debug, Default)]
+pub struct Subscriptions(Mutex<HashSet<String>>);
+
+impl Subscriptions {
+    pub fn insert(&self, uri: impl Into<String>) -> bool {
+        self.0.lock().unwrap().insert(uri.into())
+    }
+
+    pub fn remove(&self, uri: &str) -> bool {
+        self.0.lock().unwrap().remove(uri)
+    }
+
+    pub fn contains(&self, uri: &str) -> bool {
+        self.0.lock().unwrap().contains(uri)
+    }
+
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+
+    pub fn uris(&self) -> Vec<String> {
+        self.0.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Notifications for the update of `uri`, or for every subscription when
+    /// updates were missed (`None`).
+    pub(crate) fn updated(&self, uri: Option<&str>) -> Vec<Notification> {
+        let uris = match uri {
+            Some(uri) if self.contains(uri) => vec![uri.to_string()],
+            Some(_) => vec![],
+            None => self.uris(),
+        };
+        uris.into_iter()
+            .map(|uri| Notification::new(RESOURCE_UPDATED, Some(json!({"uri": uri}))))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Concrete resources by URI.
+    fixed: BTreeMap<String, ResourceDescriptor>,
+    /// Templated resources by template string.
+    templated: BTreeMap<String, (UriTemplate, ResourceDescriptor)>,
+}
+
+pub struct ResourceRegistry {
+    inner: RwLock<Inner>,
+    app_state: Arc<dyn Any + Send + Sync>,
+    updates: broadcast::Sender<String>,
+}
+
+impl ResourceRegistry {
+    pub fn empty_with_state(app_state: Arc<dyn Any + Send + Sync>) -> Arc<Self> {
+        let (updates, _) = broadcast::channel(64);
+        Arc::new(Self { inner: Default::default(), app_state, updates })
+    }
+
+    pub fn gather_with_state(app_state: Arc<dyn Any + Send + Sync>) -> Arc<Self> {
+        let reg = Self::empty_with_state(app_state);
+        for item in RESOURCES {
+            let desc = ResourceDescriptor {
+                uri: item.uri.to_string(),
+                name: item.name.to_string(),
+                description: item.description.map(str::to_string),
+                mime_type: item.mime_type.map(str::to_string),
+                handler: (item.build_handler)(),
+                completions: (item.completions)()
+                    .into_iter()
+                    .map(|(var, provider)| (var.to_string(), provider))
+                    .collect(),
+            };
+            if let Ok(template) = UriTemplate::parse(item.uri)
+                && let Some(var) = desc.completions.keys().find(|v| !template.variables().any(|t| t == *v))
+            {
+                panic!(
+                    "MCP resource '{}' has no variable '{var}' to complete\nat {}:{}",
+                    item.uri, item.defined_at_file, item.defined_at_line
+                );
+            }
+            match futures::executor::block_on(reg.insert(desc)) {
+                Ok(None) => {}
+                Ok(Some(prev)) => panic!(
+                    "duplicate MCP resource '{}'\nfirst defined previously, now again at {}:{}",
+                    prev.uri, item.defined_at_file, item.defined_at_line
+                ),
+                Err(e) => panic!("{e}\nat {}:{}", item.defined_at_file, item.defined_at_line),
+            }
+        }
+        reg
+    }
+
+    /// Add a resource or a URI-template resource; returns the one it replaced.
+    pub async fn insert(
+        &self,
+        desc: ResourceDescriptor,
+    ) -> Result<Option<ResourceDescriptor>, InvalidTemplate> {
+        let template = UriTemplate::parse(&desc.uri)?;
+        let mut inner = self.inner.write().await;
+        Ok(if template.is_literal() {
+            inner.fixed.insert(desc.uri.clone(), desc)
+        } else {
+            inner.templated.insert(desc.uri.clone(), (template, desc)).map(|(_, d)| d)
+        })
+    }
+
+    /// Remove by URI (or by template string for templated resources).
+    pub async fn remove(&self, uri: &str) -> Option<ResourceDescriptor> {
+        let mut inner = self.inner.write().await;
+        inner
+            .fixed
+            .remove(uri)
+            .or_else(|| inner.templated.remove(uri).map(|(_, d)| d))
+    }
+
+    /// Concrete resources, sorted by URI.
+    pub async fn list_page(
+        &self,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<Page<ResourceDescriptor>, InvalidCursor> {
+        let all = self.inner.read().await.fixed.values().cloned().collect();
+        paginate(all, |d| &d.uri, cursor, page_size)
+    }
+
+    /// Resource templates, sorted by template string.
+    pub async fn templates_page(
+        &self,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<Page<ResourceDescriptor>, InvalidCursor> {
+        let all = self.inner.read().await.templated.values().map(|(_, d)| d.clone()).collect();
+        paginate(all, |d| &d.uri, cursor, page_size)
+    }
+
+    /// The templated resource registered under `template`.
+    pub async fn template(&self, template: &str) -> Option<ResourceDescriptor> {
+        self.inner.read().await.templated.get(template).map(|(_, d)| d.clone())
+    }
+
+    /// Whether `uri` names a resource, directly or through a template.
+    pub async fn contains(&self, uri: &str) -> bool {
+        let inner = self.inner.read().await;
+        inner.fixed.contains_key(uri) || inner.templated.values().any(|(t, _)| t.matches(uri).is_some())
+    }
+
+    /// Read `uri`: an exact match wins, then the first matching template.
+    pub async fn read(&self, uri: &str) -> Result<Vec<ResourceContents>, ResourceError> {
+        self.read_with(uri, ToolCtx::new(self.app_state.clone())).await
+    }
+
+    pub async fn read_with(
+        &self,
+        uri: &str,
+        ctx: ToolCtx,
+    ) -> Result<Vec<ResourceContents>, ResourceError> {
+        let (handler, vars) = {
+            let inner = self.inner.read().await;
+            match inner.fixed.get(uri) {
+                Some(d) => (d.handler.clone(), UriVars::new()),
+                None => inner
+                    .templated
+                    .values()
+                    .find_map(|(t, d)| t.matches(uri).map(|vars| (d.handler.clone(), vars)))
+                    .ok_or_else(|| ResourceError::NotFound(uri.to_string()))?,
+            }
+        };
+        handler.read(&ctx, uri, &vars).await
+    }
+
+    /// The `resources` capability advertised during `initialize`.
+    pub fn capability(&self) -> ResourcesCapability {
+        ResourcesCapability { subscribe: true, ..Default::default() }
+    }
+
+    pub fn notifier(&self) -> ResourceNotifier {
+        ResourceNotifier { updates: self.updates.clone() }
+    }
+
+    /// URIs passed to [`ResourceNotifier::updated`], for the transports.
+    pub fn subscribe_updates(&self) -> broadcast::Receiver<String> {
+        self.updates.subscribe()
+    }
+
+    pub fn app_state(&self) -> Arc<dyn Any + Send + Sync> {
+        self.app_state.clone()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceMeta<'a> {
+    uri: &'a str,
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mime_type: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TemplateMeta<'a> {
+    uri_template: &'a str,
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mime_type: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct UriParams {
+    uri: String,
+}
+
+fn with_cursor(mut result: Value, next_cursor: Option<String>) -> Value {
+    if let Some(next) = next_cursor {
+        result["nextCursor"] = json!(next);
+    }
+    result
+}
+
+/// Serve a `resources/*` request; shared by the transports. `subscriptions`
+/// belong to the client, if the transport keeps any.
+pub(crate) async fn dispatch(
+    resources: &ResourceRegistry,
+    method: &str,
+    params: Option<Value>,
+    ctx: ToolCtx,
+    subscriptions: Option<&Subscriptions>,
+    page_size: usize,
+) -> Result<Value, ErrorObject> {
+    match method {
+        "resources/list" => {
+            let ListParams { cursor } = jsonrpc::parse_params(params)?;
+            let page = resources.list_page(cursor.as_deref(), page_size).await?;
+            let items: Vec<_> = page
+                .items
+                .iter()
+                .map(|d| ResourceMeta {
+                    uri: &d.uri,
+                    name: &d.name,
+                    description: d.description.as_deref(),
+                    mime_type: d.mime_type.as_deref(),
+                })
+                .collect();
+            Ok(with_cursor(json!({"resources": items}), page.next_cursor))
+        }
+        "resources/templates/list" => {
+            let ListParams { cursor } = jsonrpc::parse_params(params)?;
+            let page = resources.templates_page(cursor.as_deref(), page_size).await?;
+            let items: Vec<_> = page
+                .items
+                .iter()
+                .map(|d| TemplateMeta {
+                    uri_template: &d.uri,
+                    name: &d.name,
+                    description: d.description.as_deref(),
+                    mime_type: d.mime_type.as_deref(),
+                })
+                .collect();
+            Ok(with_cursor(json!({"resourceTemplates": items}), page.next_cursor))
+        }
+        "resources/read" => {
+            let UriParams { uri } = jsonrpc::parse_params(params)?;
+            let contents = resources.read_with(&uri, ctx).await?;
+            Ok(json!({"contents": contents}))
+        }
+        "resources/subscribe" | "resources/unsubscribe" => {
+            let Some(subscriptions) = subscriptions else {
+                return Err(ErrorObject::invalid_request("subscriptions need a session"));
+            };
+            let UriParams { uri } = jsonrpc::parse_params(params)?;
+            if method == "resources/unsubscribe" {
+                subscriptions.remove(&uri);
+            } else if resources.contains(&uri).await {
+                subscriptions.insert(uri);
+            } else {
+                return Err(ResourceError::NotFound(uri).into());
+            }
+            Ok(json!({}))
+        }
+        other => Err(ErrorObject::method_not_found(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Greeting;
+    #[async_trait]
+    impl ResourceHandler for Greeting {
+        async fn read(
+            &self,
+            _ctx: &ToolCtx,
+            uri: &str,
+            vars: &UriVars,
+        ) -> Result<Vec<ResourceContents>, ResourceError> {
+            let who = vars.get("name").map(String::as_str).unwrap_or("world");
+            Ok(vec![ResourceContents::text(uri, format!("hello {who}"))])
+        }
+    }
+
+    fn desc(uri: &str) -> ResourceDescriptor {
+        ResourceDescriptor {
+            uri: uri.into(),
+            name: "greeting".into(),
+            description: None,
+            mime_type: Some("text/plain".into()),
+            handler: Arc::new(Greeting),
+            completions: Default::default(),
+        }
+    }
+
+    #[test]
+    fn path_params_from_vars() {
+        #[derive(Deserialize)]
+        struct Ids {
+            user: u32,
+            post: String,
+        }
+        let vars: UriVars = [("user".into(), "7".into()), ("post".into(), "intro".into())].into();
+        let ids: Ids = path_from_vars("x://", &vars).unwrap();
+        assert_eq!((ids.user, ids.post.as_str()), (7, "intro"));
+
+        let one: UriVars = [("id".into(), "42".into())].into();
+        assert_eq!(path_from_vars::<u64>("x://", &one).unwrap(), 42);
+        assert!(matches!(path_from_vars::<u64>("x://", &vars), Err(ResourceError::NotFound(_))));
+    }
+
+    #[test]
+    fn missed_updates_cover_every_subscription() {
+        let subs = Subscriptions::default();
+        subs.insert("a://1");
+        subs.insert("a://2");
+        assert_eq!(subs.updated(Some("a://1")).len(), 1);
+        assert!(subs.updated(Some("a://3")).is_empty());
+        assert_eq!(subs.updated(None).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn exact_uris_win_over_templates() {
+        let r = ResourceRegistry::empty_with_state(Arc::new(()));
+        r.insert(desc("greet://{name}")).await.unwrap();
+        r.insert(desc("greet://default")).await.unwrap();
+
+        let read = |uri: &'static str| {
+            let r = r.clone();
+            async move { r.read(uri).await.unwrap().remove(0).body }
+        };
+        assert_eq!(read("greet://default").await, ResourceBody::Text("hello world".into()));
+        assert_eq!(read("greet://ada").await, ResourceBody::Text("hello ada".into()));
+        assert!(matches!(r.read("other://x").await, Err(ResourceError::NotFound(_))));
+        assert!(r.insert(desc("greet://{bad")).await.is_err());
+    }
+
+    #[test]
+    fn blob_contents_are_base64() {
+        let c = ResourceContents::blob("bin://x", [0u8, 1, 2]).with_mime_type("application/octet-stream");
+        assert_eq!(
+            serde_json::to_value(c).unwrap(),
+            json!({"uri":"bin://x","mimeType":"application/octet-stream","blob":"AAEC"})
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Client roots (`roots/list`): the directories a client considers in scope.
+//!
+//! Roots are fetched from clients that offer them once the handshake completes
+//! and cached per client; `notifications/roots/list_changed` drops the cache and
+//! fetches them again. A tool asking before a fetch has landed fetches them
+//! itself.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::uri_template::percent_decode;
+
+pub const LIST_ROOTS: &str = "roots/list";
+pub const ROOTS_LIST_CHANGED: &str = "notifications/roots/list_changed";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Root {
+    /// A `file://` URI.
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Root {
+    /// The local path of a `file://` root.
+    pub fn path(&self) -> Option<PathBuf> {
+        let rest = self.uri.strip_prefix("file://")?;
+        let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+        if !rest.starts_with('/') {
+            return None;
+        }
+        let decoded = percent_decode(rest)?;
+        // `file:///C:/work` names `C:/work`.
+        let drive = decoded.as_bytes().get(1..3).is_some_and(|b| b[0].is_ascii_alphabetic() && b[1] == b':');
+        if cfg!(windows) && drive {
+            return Some(PathBuf::from(&decoded[1..]));
+        }
+        Some(PathBuf::from(decoded))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ListRootsResult {
+    pub roots: Vec<Root>,
+}
+
+/// One client's roots, as last fetched.
+#[derive(Debug, Default)]
+pub struct RootsCache {
+    /// Bumped on every invalidation so a fetch that raced one isn't stored.
+    state: Mutex<(u64, Option<Vec<Root>>)>,
+}
+
+impl RootsCache {
+    pub fn get(&self) -> Option<Vec<Root>> {
+        self.state.lock().unwrap().1.clone()
+    }
+
+    pub fn invalidate(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.0 += 1;
+        state.1 = None;
+    }
+
+    pub(crate) fn generation(&self) -> u64 {
+        self.state.lock().unwrap().0
+    }
+
+    pub(crate) fn store(&self, generation: u64, roots: Vec<Root>) {
+        let mut state = self.state.lock().unwrap();
+        if state.0 == generation {
+            state.1 = Some(roots);
+        }
+    }
+}
+
+/// Whether `path` lies inside one of `roots`.
+///
+/// Paths are compared lexically after resolving `.` and `..`; relative paths
+/// never match. Canonicalize first if symlinks must not lead out of a root.
+pub fn contains(roots: &[Root], path: impl AsRef<Path>) -> bool {
+    let Some(path) = normalize(path.as_ref()) else {
+        return false;
+    };
+    roots.iter().filter_map(|r| r.path().and_then(|p| normalize(&p))).any(|root| path.starts_with(root))
+}
+
+fn normalize(path: &Path) -> Option<PathBuf> {
+    if !path.is_absolute() {
+        return None;
+    }
+    let mut out = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            c => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root(uri: &str) -> Root {
+        Root { uri: uri.into(), name: None }
+    }
+
+    #[test]
+    fn file_uris_become_paths() {
+        assert_eq!(root("file:///home/me/My%20Project").path(), Some(PathBuf::from("/home/me/My Project")));
+        assert_eq!(root("file://localhost/srv").path(), Some(PathBuf::from("/srv")));
+        assert_eq!(root("https://example.com/x").path(), None);
+        assert_eq!(root("file://host/share").path(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn containment_is_by_component() {
+        let roots = [root("file:///work/app"), root("file:///tmp")];
+        assert!(contains(&roots, "/work/app"));
+        assert!(contains(&roots, "/work/app/src/../Cargo.toml"));
+        assert!(contains(&roots, "/tmp/./x"));
+        assert!(!contains(&roots, "/work/application"));
+        assert!(!contains(&roots, "/work/app/../secrets"));
+        assert!(!contains(&roots, "work/app/src"));
+        assert!(!contains(&[], "/work/app"));
+    }
+}
@@ -0,0 +1,440 @@
+//! The protocol core shared by every transport.
+//!
+//! [`McpServer`] answers decoded JSON-RPC messages for one client, given that
+//! client's [`Session`]. Transports only move bytes: HTTP maps sessions onto
+//! `Mcp-Session-Id` and streams over SSE, stdio keeps one session per process.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+
+use crate::cancel::{CANCELLED_NOTIFICATION, InFlightGuard};
+use crate::completion;
+use crate::jsonrpc::{self, ErrorObject, Message, Notification, RequestMeta};
+use crate::lifecycle::{
+    CompletionsCapability, INITIALIZED_NOTIFICATION, InitializeParams, Lifecycle, LoggingCapability,
+    ServerCapabilities, ServerInfo,
+};
+use crate::logging;
+use crate::pagination::{DEFAULT_PAGE_SIZE, ListParams};
+use crate::prompt::{self, PromptRegistry};
+use crate::registry::{TOOLS_LIST_CHANGED, ToolRegistry};
+use crate::resource::{self, ResourceRegistry};
+use crate::roots::ROOTS_LIST_CHANGED;
+use crate::session::Session;
+use crate::tool::{self, Tool, ToolCtx};
+
+#[derive(Deserialize)]
+struct CallParams {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+    #[serde(default, rename = "_meta")]
+    meta: RequestMeta,
+}
+
+/// Everything a transport serves: tools plus optional resources and prompts.
+#[derive(Clone)]
+pub struct McpServer {
+    registry: Arc<ToolRegistry>,
+    resources: Option<Arc<ResourceRegistry>>,
+    prompts: Option<Arc<PromptRegistry>>,
+    server_info: Arc<ServerInfo>,
+    page_size: usize,
+    standalone_stream: bool,
+}
+
+impl McpServer {
+    pub fn new(registry: Arc<ToolRegistry>) -> Self {
+        Self {
+            registry,
+            resources: None,
+            prompts: None,
+            server_info: Default::default(),
+            page_size: DEFAULT_PAGE_SIZE,
+            standalone_stream: true,
+        }
+    }
+
+    /// Serve `resources/*`; without it those methods are not found.
+    pub fn with_resources(mut self, resources: Option<Arc<ResourceRegistry>>) -> Self {
+        self.resources = resources;
+        self
+    }
+
+    /// Serve `prompts/*`; without it those methods are not found.
+    pub fn with_prompts(mut self, prompts: Option<Arc<PromptRegistry>>) -> Self {
+        self.prompts = prompts;
+        self
+    }
+
+    pub fn with_server_info(mut self, server_info: ServerInfo) -> Self {
+        self.server_info = Arc::new(server_info);
+        self
+    }
+
+    /// Items per `*/list` page; `0` returns everything at once.
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Whether the session's outbox reaches the client outside any request.
+    /// When it doesn't, notifications that would need it are not advertised
+    /// and tools' requests to the client fail unless the call is streamed.
+    pub fn with_standalone_stream(mut self, enabled: bool) -> Self {
+        self.standalone_stream = enabled;
+        self
+    }
+
+    pub fn registry(&self) -> &Arc<ToolRegistry> {
+        &self.registry
+    }
+
+    /// Handle one message from the client; only requests produce a response.
+    ///
+    /// A `tools/call` is registered for `notifications/cancelled` right away,
+    /// before the returned future first runs, so a transport may spawn it and
+    /// still have a cancel that follows it find it. Messages the handler emits
+    /// while running go to `response_stream` when given, else to the session.
+    ///
+    /// Without a session the handshake is not enforced and `initialize` is
+    /// answered as if it came from a fresh client.
+    pub fn handle(
+        &self,
+        session: Option<Arc<Session>>,
+        msg: Message,
+        response_stream: Option<mpsc::UnboundedSender<Value>>,
+    ) -> impl Future<Output = Option<jsonrpc::Response>> + Send + 'static {
+        let tracked = match (&msg, &session) {
+            (Message::Request(req), Some(s)) if req.method == "tools/call" => {
+                Some(s.in_flight().start(req.id.clone()))
+            }
+            _ => None,
+        };
+        let server = self.clone();
+        async move {
+            match msg {
+                Message::Request(req) => {
+                    let id = req.id.clone();
+                    match server.dispatch(session.as_ref(), req, tracked, response_stream).await {
+                        Err(e) if e.is_cancelled() => None,
+                        result => Some(jsonrpc::Response::from_result(id, result)),
+                    }
+                }
+                Message::Notification(n) => {
+                    if let Some(session) = &session {
+                        server.on_notification(session, n);
+                    }
+                    None
+                }
+                // Answers to our own requests; nothing to send back.
+                Message::Response(resp) => {
+                    if let Some(session) = &session {
+                        session.pending_requests().resolve(resp);
+                    }
+                    None
+                }
+            }
+        }
+    }
+
+    fn capabilities(&self) -> ServerCapabilities {
+        let mut tools = self.registry.capability();
+        let mut resources = self.resources.as_ref().map(|r| r.capability());
+        if !self.standalone_stream {
+            tools.list_changed = false;
+            if let Some(resources) = &mut resources {
+                resources.subscribe = false;
+                resources.list_changed = false;
+            }
+        }
+        ServerCapabilities {
+            tools: Some(tools),
+            resources,
+            prompts: self.prompts.as_ref().map(|p| p.capability()),
+            completions: (self.prompts.is_some() || self.resources.is_some())
+                .then(CompletionsCapability::default),
+            logging: Some(LoggingCapability::default()),
+        }
+    }
+
+    async fn dispatch(
+        &self,
+        session: Option<&Arc<Session>>,
+        req: jsonrpc::Request,
+        tracked: Option<InFlightGuard>,
+        response_stream: Option<mpsc::UnboundedSender<Value>>,
+    ) -> Result<Value, ErrorObject> {
+        if let Some(session) = session {
+            session.lifecycle().check_request(&req.method)?;
+        }
+        match req.method.as_str() {
+            "initialize" => {
+                let params: InitializeParams = jsonrpc::parse_params(req.params)?;
+                let (info, capabilities) = (&self.server_info, self.capabilities());
+                let result = match session {
+                    Some(s) => s.lifecycle().initialize(params, info, capabilities),
+                    None => Lifecycle::new().initialize(params, info, capabilities),
+                };
+                if let Some(session) = session {
+                    self.forward_notifications(session);
+                }
+                Ok(serde_json::to_value(result).unwrap())
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => {
+                let ListParams { cursor } = jsonrpc::parse_params(req.params)?;
+                let page = self.registry.list_page(cursor.as_deref(), self.page_size).await?;
+                let tools: Vec<Tool> = page.items.iter().map(Tool::from).collect();
+                let mut result = json!({"tools": tools});
+                if let Some(next) = page.next_cursor {
+                    result["nextCursor"] = json!(next);
+                }
+                Ok(result)
+            }
+            "tools/call" => {
+                let CallParams { name, arguments, meta } = jsonrpc::parse_params(req.params)?;
+                // The token is also cancelled when this future is dropped,
+                // e.g. on client disconnect.
+                let token = tracked.as_ref().map(|t| t.token().clone()).unwrap_or_default();
+                let _cancel_on_drop = token.clone().drop_guard();
+                let mut ctx = ToolCtx::new(self.registry.app_state())
+                    .with_progress_token(meta.progress_token)
+                    .with_cancellation(token);
+                if let Some(session) = session {
+                    let caps = session.lifecycle().client_capabilities().clone();
+                    ctx = ctx
+                        .with_session(session.clone())
+                        .with_log_level(session.log_level().clone())
+                        .with_peer(session.pending_requests().clone(), caps)
+                        .with_roots(session.roots().clone());
+                }
+                match response_stream {
+                    Some(tx) => ctx = ctx.with_response_stream(tx),
+                    None if !self.standalone_stream => ctx = ctx.without_client_channel(),
+                    None => {}
+                }
+                let result = self.registry.call_with(&name, arguments, ctx).await;
+                Ok(serde_json::to_value(tool::execution_error_as_result(result)?).unwrap())
+            }
+            method if method.starts_with("resources/") => {
+                let Some(resources) = &self.resources else {
+                    return Err(ErrorObject::method_not_found(method));
+                };
+                let mut ctx = ToolCtx::new(resources.app_state());
+                if let Some(session) = session {
+                    ctx = ctx.with_session(session.clone());
+                }
+                let subscriptions = session.map(|s| s.subscriptions());
+                resource::dispatch(resources, method, req.params, ctx, subscriptions, self.page_size)
+                    .await
+            }
+            method if method.starts_with("prompts/") => {
+                let Some(prompts) = &self.prompts else {
+                    return Err(ErrorObject::method_not_found(method));
+                };
+                let mut ctx = ToolCtx::new(prompts.app_state());
+                if let Some(session) = session {
+                    ctx = ctx.with_session(session.clone());
+                }
+                prompt::dispatch(prompts, method, req.params, ctx, self.page_size).await
+            }
+            "logging/setLevel" => {
+                let session = session
+                    .ok_or_else(|| ErrorObject::invalid_request("log levels need a session"))?;
+                logging::set_level(session.log_level(), req.params)
+            }
+            "completion/complete" if self.prompts.is_some() || self.resources.is_some() => {
+                completion::dispatch(self.prompts.as_deref(), self.resources.as_deref(), req.params)
+                    .await
+            }
+            other => Err(ErrorObject::method_not_found(other)),
+        }
+    }
+
+    /// Relay tool list changes and subscribed resource updates to the
+    /// session's outbox until the session closes.
+    fn forward_notifications(&self, session: &Arc<Session>) {
+        if !self.standalone_stream {
+            return;
+        }
+        if session.lifecycle().wants_tools_list_changed() {
+            forward_list_changes(&self.registry, session.clone());
+        }
+        if let Some(resources) = &self.resources {
+            forward_resource_updates(resources, session.clone());
+        }
+    }
+
+    fn on_notification(&self, session: &Arc<Session>, n: Notification) {
+        session.lifecycle().on_notification(&n.method);
+        match n.method.as_str() {
+            CANCELLED_NOTIFICATION => session.in_flight().on_cancelled(n.params),
+            INITIALIZED_NOTIFICATION => self.fetch_roots(session),
+            ROOTS_LIST_CHANGED => {
+                session.roots().invalidate();
+                self.fetch_roots(session);
+            }
+            _ => {}
+        }
+    }
+
+    /// Fill the session's roots cache in the background, so tools asking for
+    /// roots don't wait on the client. Only for clients that offer `roots`.
+    fn fetch_roots(&self, session: &Arc<Session>) {
+        if !self.standalone_stream {
+            return;
+        }
+        let caps = session.lifecycle().client_capabilities().clone();
+        let ctx = ToolCtx::new(self.registry.app_state())
+            .with_session(session.clone())
+            .with_peer(session.pending_requests().clone(), caps)
+            .with_roots(session.roots().clone());
+        if !ctx.client_supports("roots") {
+            return;
+        }
+        let session = session.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = session.closed().cancelled() => {}
+                _ = ctx.roots() => {}
+            }
+        });
+    }
+}
+
+fn forward_list_changes(registry: &ToolRegistry, session: Arc<Session>) {
+    let mut changes = registry.subscribe();
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = session.closed().cancelled() => break,
+                change = changes.recv() => match change {
+                    Ok(()) | Err(RecvError::Lagged(_)) => {
+                        // One notification covers a burst of changes.
+                        while changes.try_recv().is_ok() {}
+                        session.notify(TOOLS_LIST_CHANGED, None);
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+    });
+}
+
+fn forward_resource_updates(resources: &ResourceRegistry, session: Arc<Session>) {
+    let mut updates = resources.subscribe_updates();
+    tokio::spawn(async move {
+        loop {
+            let uri = tokio::select! {
+                _ = session.closed().cancelled() => break,
+                update = updates.recv() => match update {
+                    Ok(uri) => Some(uri),
+                    // Missed some; a spurious update is better than a lost one.
+                    Err(RecvError::Lagged(_)) => None,
+                    Err(RecvError::Closed) => break,
+                },
+            };
+            for n in session.subscriptions().updated(uri.as_deref()) {
+                session.outbox().push(&n);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonrpc::ResponsePayload;
+    use crate::tool::{CallToolResult, ToolDescriptor, ToolError, ToolHandler};
+    use async_trait::async_trait;
+
+    struct Echo;
+    #[async_trait]
+    impl ToolHandler for Echo {
+        async fn call(&self, _ctx: &ToolCtx, args: Value) -> Result<CallToolResult, ToolError> {
+            Ok(args.into())
+        }
+    }
+
+    async fn server() -> McpServer {
+        let registry = ToolRegistry::empty_with_state(Arc::new(()));
+        registry
+            .insert(ToolDescriptor {
+                name: "echo",
+                description: None,
+                input_schema: schemars::schema_for!(Value),
+                output_schema: schemars::schema_for!(Value),
+                handler: Arc::new(Echo),
+                structured: true,
+                title: None,
+                annotations: Default::default(),
+            })
+            .await;
+        McpServer::new(registry)
+    }
+
+    async fn initialized(server: &McpServer) -> Arc<Session> {
+        let session = Arc::new(Session::new("s", 8));
+        let init = json!({"jsonrpc":"2.0","id":0,"method":"initialize","params":{
+            "protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"t","version":"0"}}});
+        assert!(send(server, &session, init).await.unwrap()["capabilities"]["tools"].is_object());
+        let initialized = json!({"jsonrpc":"2.0","method":"notifications/initialized"});
+        assert!(send(server, &session, initialized).await.is_none());
+        session
+    }
+
+    async fn send(server: &McpServer, session: &Arc<Session>, msg: Value) -> Option<Value> {
+        let msg = Message::parse(msg).unwrap();
+        let resp = server.handle(Some(session.clone()), msg, None).await?;
+        Some(match resp.payload {
+            ResponsePayload::Result(v) => v,
+            ResponsePayload::Error(e) => json!({"error": e.code}),
+        })
+    }
+
+    #[tokio::test]
+    async fn handshake_then_tools() {
+        let server = server().await;
+        let session = Arc::new(Session::new("s", 8));
+        let list = json!({"jsonrpc":"2.0","id":1,"method":"tools/list"});
+        assert_eq!(send(&server, &session, list.clone()).await.unwrap()["error"], -32003);
+
+        let session = initialized(&server).await;
+        assert_eq!(send(&server, &session, list).await.unwrap()["tools"][0]["name"], "echo");
+        let call = json!({"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"echo","arguments":{"a":1}}});
+        assert_eq!(send(&server, &session, call).await.unwrap()["structuredContent"], json!({"a":1}));
+        let nope = json!({"jsonrpc":"2.0","id":3,"method":"nope"});
+        assert_eq!(send(&server, &session, nope).await.unwrap()["error"], -32601);
+    }
+
+    #[tokio::test]
+    async fn push_capabilities_need_a_standalone_stream() {
+        let init = json!({"jsonrpc":"2.0","id":0,"method":"initialize","params":{
+            "protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"t","version":"0"}}});
+        for (standalone, tools) in [(true, json!({"listChanged": true})), (false, json!({}))] {
+            let server = server().await.with_standalone_stream(standalone);
+            let session = Arc::new(Session::new("s", 8));
+            let result = send(&server, &session, init.clone()).await.unwrap();
+            assert_eq!(result["capabilities"]["tools"], tools);
+        }
+    }
+
+    #[tokio::test]
+    async fn calls_are_cancellable_before_they_start() {
+        let server = server().await;
+        let session = initialized(&server).await;
+        let call = json!({"jsonrpc":"2.0","id":7,"method":"tools/call","params":{"name":"echo"}});
+        let pending = server.handle(Some(session.clone()), Message::parse(call).unwrap(), None);
+        assert_eq!(session.in_flight().len(), 1);
+        let cancel = json!({"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":7}});
+        server.handle(Some(session.clone()), Message::parse(cancel).unwrap(), None).await;
+        assert!(pending.await.is_none());
+        assert!(session.in_flight().is_empty());
+    }
+}
@@ -0,0 +1,267 @@
+//! Per-client sessions for the Streamable HTTP transport (`Mcp-Session-Id`).
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tokio_util::sync::DropGuard;
+
+use crate::cancel::{CancellationToken, InFlight};
+use crate::jsonrpc::Notification;
+use crate::lifecycle::Lifecycle;
+use crate::logging::LogLevel;
+use crate::outbound::{DEFAULT_EVENT_BUFFER, Outbox};
+use crate::peer::PendingRequests;
+use crate::resource::Subscriptions;
+use crate::roots::RootsCache;
+
+pub const SESSION_HEADER: &str = "Mcp-Session-Id";
+
+/// State kept for one connected client.
+pub struct Session {
+    id: String,
+    lifecycle: Mutex<Lifecycle>,
+    extensions: Mutex<http::Extensions>,
+    outbox: Outbox,
+    in_flight: Arc<InFlight>,
+    subscriptions: Subscriptions,
+    log_level: LogLevel,
+    pending_requests: Arc<PendingRequests>,
+    roots: Arc<RootsCache>,
+    closed: CancellationToken,
+    last_seen: Mutex<Instant>,
+}
+
+impl Session {
+    pub fn new(id: impl Into<String>, event_buffer: usize) -> Self {
+        Self {
+            id: id.into(),
+            lifecycle: Mutex::new(Lifecycle::new()),
+            extensions: Mutex::new(http::Extensions::new()),
+            outbox: Outbox::new(event_buffer),
+            in_flight: Default::default(),
+            subscriptions: Default::default(),
+            log_level: Default::default(),
+            pending_requests: Default::default(),
+            roots: Default::default(),
+            closed: CancellationToken::new(),
+            last_seen: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn lifecycle(&self) -> MutexGuard<'_, Lifecycle> {
+        self.lifecycle.lock().unwrap()
+    }
+
+    /// Typed per-session storage for tools, e.g. a login or a scratch cursor.
+    pub fn extensions(&self) -> MutexGuard<'_, http::Extensions> {
+        self.extensions.lock().unwrap()
+    }
+
+    /// Messages queued for the client, streamed over the GET SSE channel.
+    pub fn outbox(&self) -> &Outbox {
+        &self.outbox
+    }
+
+    /// Requests of this client that are still running.
+    pub fn in_flight(&self) -> &Arc<InFlight> {
+        &self.in_flight
+    }
+
+    /// Resources the client asked to be told about when they change.
+    pub fn subscriptions(&self) -> &Subscriptions {
+        &self.subscriptions
+    }
+
+    /// Set by `logging/setLevel`; applies to the client's tool calls.
+    pub fn log_level(&self) -> &LogLevel {
+        &self.log_level
+    }
+
+    /// Requests sent to the client that still wait for its response.
+    pub fn pending_requests(&self) -> &Arc<PendingRequests> {
+        &self.pending_requests
+    }
+
+    /// The client's roots as last fetched; dropped on `notifications/roots/list_changed`.
+    pub fn roots(&self) -> &Arc<RootsCache> {
+        &self.roots
+    }
+
+    /// Push a server-initiated JSON-RPC notification to the client.
+    pub fn notify(&self, method: &str, params: Option<Value>) {
+        self.outbox.push(&Notification::new(method, params));
+    }
+
+    /// End the session's streams and running requests; called when it is
+    /// deleted or expires.
+    pub fn close(&self) {
+        self.outbox.close();
+        self.in_flight.cancel_all();
+        self.subscriptions.clear();
+        self.pending_requests.close();
+        self.closed.cancel();
+    }
+
+    /// Fires once the session is closed; background work tied to it stops then.
+    pub fn closed(&self) -> &CancellationToken {
+        &self.closed
+    }
+
+    fn touch(&self) {
+        *self.last_seen.lock().unwrap() = Instant::now();
+    }
+
+    /// Idle past `ttl` with nothing running: no request in flight, no open
+    /// stream and nothing sent to the client for that long.
+    fn expired(&self, now: Instant, ttl: Duration) -> bool {
+        let last_active = (*self.last_seen.lock().unwrap()).max(self.outbox.last_push());
+        let busy = !self.in_flight.is_empty() || self.outbox.has_live_stream();
+        !busy && now.saturating_duration_since(last_active) > ttl
+    }
+}
+
+/// Issues session ids and reclaims sessions idle for longer than `ttl`.
+///
+/// Created inside a Tokio runtime, it also sweeps in the background for as
+/// long as it lives, so expired sessions are closed on an idle server too.
+pub struct SessionManager {
+    shared: Arc<Shared>,
+    event_buffer: usize,
+    _sweeper: Option<DropGuard>,
+}
+
+struct Shared {
+    sessions: Mutex<HashMap<String, Arc<Session>>>,
+    ttl: Duration,
+}
+
+impl SessionManager {
+    pub fn new(ttl: Duration) -> Self {
+        let shared = Arc::new(Shared { sessions: Default::default(), ttl });
+        let sweeper = tokio::runtime::Handle::try_current().ok().map(|rt| {
+            let stop = CancellationToken::new();
+            rt.spawn(sweep_periodically(Arc::downgrade(&shared), stop.clone()));
+            stop.drop_guard()
+        });
+        Self { shared, event_buffer: DEFAULT_EVENT_BUFFER, _sweeper: sweeper }
+    }
+
+    /// How many outbound events each session keeps for `Last-Event-ID` replay.
+    pub fn with_event_buffer(mut self, event_buffer: usize) -> Self {
+        self.event_buffer = event_buffer;
+        self
+    }
+
+    pub fn create(&self) -> Arc<Session> {
+        let session = Arc::new(Session::new(new_session_id(), self.event_buffer));
+        let mut sessions = self.shared.sessions.lock().unwrap();
+        self.shared.sweep(&mut sessions);
+        sessions.insert(session.id().to_string(), session.clone());
+        session
+    }
+
+    /// Look up a live session and mark it as used. Expired sessions are gone.
+    pub fn get(&self, id: &str) -> Option<Arc<Session>> {
+        let mut sessions = self.shared.sessions.lock().unwrap();
+        self.shared.sweep(&mut sessions);
+        let session = sessions.get(id).cloned()?;
+        session.touch();
+        Some(session)
+    }
+
+    pub fn remove(&self, id: &str) -> Option<Arc<Session>> {
+        let session = self.shared.sessions.lock().unwrap().remove(id)?;
+        session.close();
+        Some(session)
+    }
+
+    pub fn len(&self) -> usize {
+        let mut sessions = self.shared.sessions.lock().unwrap();
+        self.shared.sweep(&mut sessions);
+        sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Shared {
+    fn sweep(&self, sessions: &mut HashMap<String, Arc<Session>>) {
+        let now = Instant::now();
+        sessions.retain(|_, s| {
+            let expired = s.expired(now, self.ttl);
+            if expired {
+                s.close();
+            }
+            !expired
+        });
+    }
+}
+
+async fn sweep_periodically(shared: Weak<Shared>, stop: CancellationToken) {
+    let Some(ttl) = shared.upgrade().map(|s| s.ttl) else {
+        return;
+    };
+    let mut tick = tokio::time::interval((ttl / 2).clamp(Duration::from_millis(10), Duration::from_secs(60)));
+    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = stop.cancelled() => break,
+            _ = tick.tick() => {}
+        }
+        let Some(shared) = shared.upgrade() else {
+            break;
+        };
+        shared.sweep(&mut shared.sessions.lock().unwrap());
+    }
+}
+
+/// 128 bits from the OS CSPRNG, hex encoded (visible ASCII as the spec requires).
+fn new_session_id() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).expect("OS random number generator unavailable");
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_get_remove() {
+        let m = SessionManager::new(Duration::from_secs(60));
+        let s = m.create();
+        assert_eq!(s.id().len(), 32);
+        assert!(m.get(s.id()).is_some());
+        assert_ne!(m.create().id(), s.id());
+        assert!(m.remove(s.id()).is_some());
+        assert!(m.get(s.id()).is_none());
+    }
+
+    #[test]
+    fn idle_sessions_expire() {
+        let m = SessionManager::new(Duration::ZERO);
+        let s = m.create();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(m.get(s.id()).is_none());
+        assert!(m.is_empty());
+    }
+
+    #[tokio::test]
+    async fn idle_server_still_closes_expired_sessions() {
+        let m = SessionManager::new(Duration::from_millis(20));
+        let idle = m.create();
+        let busy = m.create();
+        let _call = busy.in_flight().start(crate::jsonrpc::RequestId::Number(1));
+        tokio::time::timeout(Duration::from_secs(2), idle.closed().cancelled()).await.unwrap();
+        assert!(!busy.closed().is_cancelled());
+        assert!(m.get(busy.id()).is_some());
+    }
+}
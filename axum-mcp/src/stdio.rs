@@ -1,96 +1,185 @@
 use std::any::Any;
+use std::collections::VecDeque;
 use std::sync::Arc;
 
-use mcp_protocol_sdk::core::error::McpError;
-use mcp_protocol_sdk::protocol::types::{self, JsonRpcRequest, JsonRpcResponse};
-use mcp_protocol_sdk::transport::stdio::StdioServerTransport;
-use mcp_protocol_sdk::transport::traits::ServerTransport;
-use serde::Deserialize;
-use serde_json::{Value, json};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
 
+use crate::jsonrpc::{self, ErrorObject, Message};
+use crate::lifecycle::ServerInfo;
+use crate::outbound::DEFAULT_EVENT_BUFFER;
+use crate::pagination::DEFAULT_PAGE_SIZE;
+use crate::prompt::{self, PromptRegistry};
 use crate::registry::ToolRegistry;
-use crate::tool::ToolError;
+use crate::resource::{self, ResourceRegistry};
+use crate::server::McpServer;
+use crate::session::Session;
 
-#[derive(serde::Serialize)]
-struct ToolMeta {
-    name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    desc: Option<&'static str>,
-    input_schema: Value,
-    output_schema: Value,
-    #[serde(rename = "structuredContent")]
-    structured: bool,
+#[derive(Clone)]
+pub struct StdioConfig {
+    pub server_info: ServerInfo,
+    /// Items per `*/list` page; `0` returns everything at once.
+    pub page_size: usize,
+    /// Resources served next to the tools; `None` leaves out `resources/*`.
+    pub resources: Option<Arc<ResourceRegistry>>,
+    /// Prompts served next to the tools; `None` leaves out `prompts/*`.
+    pub prompts: Option<Arc<PromptRegistry>>,
 }
 
-#[derive(Deserialize)]
-struct CallParams {
-    name: String,
-    #[serde(default)]
-    arguments: Value,
+impl Default for StdioConfig {
+    fn default() -> Self {
+        Self {
+            server_info: ServerInfo::default(),
+            page_size: DEFAULT_PAGE_SIZE,
+            resources: None,
+            prompts: None,
+        }
+    }
 }
 
+/// Serve the tools in `registry` plus every `#[mcp_resource]` and
+/// `#[mcp_prompt]` in the binary, sharing `state` with them.
 pub async fn run_stdio(
     registry: Arc<ToolRegistry>,
-    _state: Arc<dyn Any + Send + Sync>,
+    state: Arc<dyn Any + Send + Sync>,
 ) -> anyhow::Result<()> {
-    let handler = {
-        let registry = registry.clone();
-        std::sync::Arc::new(move |req: JsonRpcRequest| {
-            let registry = registry.clone();
-            Box::pin(async move {
-                match req.method.as_str() {
-                    "tools/list" => {
-                        let list = registry.list().await;
-                        let tools: Vec<_> = list
-                            .into_iter()
-                            .map(|(name, desc, i, o)| ToolMeta {
-                                name,
-                                desc,
-                                input_schema: serde_json::to_value(i).unwrap(),
-                                output_schema: serde_json::to_value(o).unwrap(),
-                                structured: true,
-                            })
-                            .collect();
-                        Ok(JsonRpcResponse {
-                            jsonrpc: types::JSONRPC_VERSION.to_string(),
-                            id: req.id,
-                            result: Some(json!({"tools": tools})),
-                        })
-                    }
-                    "tools/call" => {
-                        let params_val = req
-                            .params
-                            .clone()
-                            .ok_or_else(|| McpError::protocol("missing params"))?;
-                        let CallParams { name, arguments } = serde_json::from_value(params_val)
-                            .map_err(|e| McpError::protocol(format!("invalid params: {e}")))?;
-                        match registry.call(&name, arguments).await {
-                            Ok(v) => Ok(JsonRpcResponse {
-                                jsonrpc: types::JSONRPC_VERSION.to_string(),
-                                id: req.id,
-                                result: Some(json!({"result": v})),
-                            }),
-                            Err(e) => Err(match e {
-                                ToolError::NotFound(n) => McpError::ToolNotFound(n),
-                                ToolError::InvalidArgs(msg) => McpError::Validation(msg),
-                                ToolError::Internal(msg) => McpError::Internal(msg),
-                            }),
-                        }
-                    }
-                    _ => Err(McpError::protocol(format!(
-                        "Method '{}' not found",
-                        req.method
-                    ))),
-                }
-            })
-                as std::pin::Pin<
-                    Box<dyn std::future::Future<Output = Result<JsonRpcResponse, McpError>> + Send>,
-                >
-        })
+    let config = StdioConfig {
+        resources: (!resource::RESOURCES.is_empty())
+            .then(|| ResourceRegistry::gather_with_state(state.clone())),
+        prompts: (!prompt::PROMPTS.is_empty()).then(|| PromptRegistry::gather_with_state(state)),
+        ..Default::default()
     };
+    run_stdio_with_config(registry, config).await
+}
+
+/// Serve one client over newline-delimited JSON-RPC on stdin/stdout.
+pub async fn run_stdio_with_config(
+    registry: Arc<ToolRegistry>,
+    config: StdioConfig,
+) -> anyhow::Result<()> {
+    serve_io_with_config(tokio::io::stdin(), tokio::io::stdout(), registry, config).await
+}
+
+/// Serve the tools in `registry` to one client over any byte stream: a pipe,
+/// a socket, a child process or an in-memory `tokio::io::duplex`.
+pub async fn serve_io<R, W>(reader: R, writer: W, registry: Arc<ToolRegistry>) -> anyhow::Result<()>
+where
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    serve_io_with_config(reader, writer, registry, StdioConfig::default()).await
+}
 
-    let mut transport = StdioServerTransport::new();
-    transport.set_request_handler(handler);
-    transport.start().await?;
+/// Newline-delimited JSON-RPC over `reader` and `writer`.
+///
+/// Everything bound for the client, responses as well as notifications sent
+/// by tools mid-call, goes through one writer task so lines never interleave.
+/// Requests run concurrently, so a slow one holds up no other and
+/// `notifications/cancelled` can reach it; on end of input the server waits
+/// for running requests, then returns.
+pub async fn serve_io_with_config<R, W>(
+    reader: R,
+    writer: W,
+    registry: Arc<ToolRegistry>,
+    config: StdioConfig,
+) -> anyhow::Result<()>
+where
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let server = McpServer::new(registry)
+        .with_resources(config.resources)
+        .with_prompts(config.prompts)
+        .with_server_info(config.server_info)
+        .with_page_size(config.page_size);
+    // One stream serves exactly one client, so one session.
+    let session = Arc::new(Session::new("stdio", DEFAULT_EVENT_BUFFER));
+    let (tx, rx) = mpsc::unbounded_channel::<Value>();
+    let writer = tokio::spawn(write_messages(writer, rx, session.clone()));
+
+    let mut calls = JoinSet::new();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        while calls.try_join_next().is_some() {}
+        if line.trim().is_empty() {
+            continue;
+        }
+        let msg = match serde_json::from_str::<Value>(&line) {
+            Err(e) => Err(jsonrpc::Response::error(None, ErrorObject::parse_error(e))),
+            Ok(value) => Message::parse(value),
+        };
+        let msg = match msg {
+            Ok(msg) => msg,
+            Err(reply) => {
+                let _ = tx.send(serde_json::to_value(reply).unwrap());
+                continue;
+            }
+        };
+        // Later requests are checked against the session `initialize` sets up.
+        let concurrent = matches!(&msg, Message::Request(req) if req.method != "initialize");
+        let handled = server.handle(Some(session.clone()), msg, Some(tx.clone()));
+        let out = tx.clone();
+        let reply = async move {
+            if let Some(reply) = handled.await {
+                let _ = out.send(serde_json::to_value(reply).unwrap());
+            }
+        };
+        // Requests may wait on the client, so keep reading while they run.
+        if concurrent {
+            calls.spawn(reply);
+        } else {
+            reply.await;
+        }
+    }
+
+    // Nobody is left to answer our requests.
+    session.pending_requests().close();
+    while calls.join_next().await.is_some() {}
+    session.close();
+    drop(tx);
+    writer.await??;
+    Ok(())
+}
+
+/// Write replies from `rx` and the session's notifications, one per line,
+/// until every sender of `rx` is gone.
+async fn write_messages(
+    mut out: impl AsyncWrite + Unpin,
+    mut rx: mpsc::UnboundedReceiver<Value>,
+    session: Arc<Session>,
+) -> anyhow::Result<()> {
+    let (replay, mut events) = session.outbox().subscribe(None);
+    let mut replay = VecDeque::from(replay);
+    let mut outbox_open = true;
+    let mut last = 0;
+    loop {
+        let line = if let Some(event) = replay.pop_front() {
+            if event.id <= last {
+                continue;
+            }
+            last = event.id;
+            session.outbox().mark_delivered(event.id);
+            event.data.as_bytes().to_vec()
+        } else {
+            tokio::select! {
+                message = rx.recv() => match message {
+                    Some(message) => serde_json::to_vec(&message)?,
+                    None => break,
+                },
+                event = events.recv(), if outbox_open => {
+                    match event {
+                        Some(event) => replay.push_back(event),
+                        None => outbox_open = false,
+                    }
+                    continue;
+                }
+            }
+        };
+        out.write_all(&line).await?;
+        out.write_all(b"\n").await?;
+        out.flush().await?;
+    }
     Ok(())
 }
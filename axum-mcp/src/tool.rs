@@ -1,9 +1,24 @@
 use std::any::Any;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
-use serde_json::Value;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use schemars::JsonSchema;
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::content::ContentBlock;
+use crate::elicitation::{ELICIT, ELICIT_TIMEOUT, ElicitAction, ElicitRequest, ElicitResult, Elicitation};
+use crate::jsonrpc::Notification;
+use crate::logging::{self, LOG_MESSAGE, LogLevel, LoggingLevel};
+use crate::peer::{self, DEFAULT_REQUEST_TIMEOUT, PeerError, PendingRequests};
+use crate::roots::{self, LIST_ROOTS, ListRootsResult, Root, RootsCache};
+use crate::sampling::{CREATE_MESSAGE, CreateMessageRequest, CreateMessageResult};
 use crate::schema::RootSchema;
+use crate::session::Session;
 
 #[derive(Clone)]
 pub struct ToolDescriptor {
@@ -13,6 +28,123 @@ pub struct ToolDescriptor {
     pub output_schema: RootSchema,
     pub handler: Arc<dyn ToolHandler + Send + Sync>,
     pub structured: bool,
+    /// Human-readable name for display; `name` stays the identifier.
+    pub title: Option<&'static str>,
+    pub annotations: ToolAnnotations,
+}
+
+/// A tool as `tools/list` describes it to clients.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tool {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Always an object schema (`"type": "object"`).
+    pub input_schema: Value,
+    /// Advertised for structured tools only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    #[serde(default, skip_serializing_if = "ToolAnnotations::is_empty")]
+    pub annotations: ToolAnnotations,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
+}
+
+impl From<&ToolDescriptor> for Tool {
+    fn from(d: &ToolDescriptor) -> Self {
+        Self {
+            name: d.name.to_string(),
+            title: d.title.map(Into::into),
+            description: d.description.map(Into::into),
+            input_schema: object_schema(&d.input_schema),
+            output_schema: d.structured.then(|| output_schema(&d.output_schema)),
+            annotations: d.annotations,
+            meta: None,
+        }
+    }
+}
+
+impl From<ToolDescriptor> for Tool {
+    fn from(d: ToolDescriptor) -> Self {
+        Self::from(&d)
+    }
+}
+
+/// Tool schemas must describe objects. Schemas without a type (e.g. that of
+/// `serde_json::Value`) get `"type": "object"`; ones of another type are
+/// replaced by an open object schema.
+fn object_schema(schema: &RootSchema) -> Value {
+    let mut value = serde_json::to_value(schema).unwrap_or_default();
+    match value.as_object_mut() {
+        Some(obj) => match obj.get("type") {
+            Some(t) if t == "object" => {}
+            None if !obj.contains_key("anyOf") && !obj.contains_key("oneOf") => {
+                obj.insert("type".into(), "object".into());
+            }
+            _ => value = json!({"type": "object"}),
+        },
+        None => value = json!({"type": "object"}),
+    }
+    value
+}
+
+/// Whether a structured tool's output is not known to be an object, so its
+/// `structuredContent` goes out as `{"result": value}`.
+pub(crate) fn wraps_output(schema: &RootSchema) -> bool {
+    match serde_json::to_value(schema).unwrap_or_default() {
+        Value::Object(obj) => match obj.get("type") {
+            Some(t) => t != "object",
+            None => obj.contains_key("anyOf") || obj.contains_key("oneOf"),
+        },
+        v => v == false,
+    }
+}
+
+/// The advertised `outputSchema`: the tool's own when it describes an
+/// object, otherwise an object with the output under `result`.
+fn output_schema(schema: &RootSchema) -> Value {
+    if !wraps_output(schema) {
+        return object_schema(schema);
+    }
+    let mut inner = serde_json::to_value(schema).unwrap_or_default();
+    let mut wrapper = serde_json::Map::new();
+    // Keep the root-level keywords at the root so `$ref`s still resolve.
+    if let Some(obj) = inner.as_object_mut() {
+        for key in ["$schema", "$defs", "definitions"] {
+            if let Some(v) = obj.remove(key) {
+                wrapper.insert(key.into(), v);
+            }
+        }
+    }
+    wrapper.insert("type".into(), "object".into());
+    wrapper.insert("properties".into(), json!({"result": inner}));
+    wrapper.insert("required".into(), json!(["result"]));
+    Value::Object(wrapper)
+}
+
+/// Hints clients use to decide, e.g., whether a call needs the user's approval.
+/// They describe the tool; nothing enforces them. Unset hints take the spec
+/// defaults (not read-only, destructive, not idempotent, open world).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destructive_hint: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idempotent_hint: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_world_hint: Option<bool>,
+}
+
+impl ToolAnnotations {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 #[derive(Clone, Default)]
@@ -21,9 +153,216 @@ pub struct ReqMeta {
     pub headers: Vec<(String, String)>,
 }
 
+#[derive(Clone)]
 pub struct ToolCtx {
     pub app_state: Arc<dyn Any + Send + Sync>,
     pub req_meta: ReqMeta,
+    /// The calling client's session, when the transport tracks one.
+    pub session: Option<Arc<Session>>,
+    /// Stream of the response to this very request, when the transport opened one.
+    response_stream: Option<mpsc::UnboundedSender<Value>>,
+    progress_token: Option<Value>,
+    cancellation: CancellationToken,
+    log_level: LogLevel,
+    /// Requests to the client and what it said it supports.
+    peer: Option<(Arc<PendingRequests>, Value)>,
+    roots: Option<Arc<RootsCache>>,
+    /// False when nothing carries requests to the client; they fail at once.
+    client_channel: bool,
+}
+
+impl ToolCtx {
+    pub fn new(app_state: Arc<dyn Any + Send + Sync>) -> Self {
+        Self {
+            app_state,
+            req_meta: Default::default(),
+            session: None,
+            response_stream: None,
+            progress_token: None,
+            cancellation: CancellationToken::new(),
+            log_level: LogLevel::default(),
+            peer: None,
+            roots: None,
+            client_channel: true,
+        }
+    }
+
+    pub fn with_session(mut self, session: Arc<Session>) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    pub(crate) fn with_response_stream(mut self, tx: mpsc::UnboundedSender<Value>) -> Self {
+        self.response_stream = Some(tx);
+        self
+    }
+
+    pub(crate) fn with_progress_token(mut self, token: Option<Value>) -> Self {
+        self.progress_token = token;
+        self
+    }
+
+    pub(crate) fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    pub(crate) fn with_log_level(mut self, level: LogLevel) -> Self {
+        self.log_level = level;
+        self
+    }
+
+    pub(crate) fn with_peer(mut self, pending: Arc<PendingRequests>, client_capabilities: Value) -> Self {
+        self.peer = Some((pending, client_capabilities));
+        self
+    }
+
+    pub(crate) fn without_client_channel(mut self) -> Self {
+        self.client_channel = false;
+        self
+    }
+
+    pub(crate) fn with_roots(mut self, roots: Arc<RootsCache>) -> Self {
+        self.roots = Some(roots);
+        self
+    }
+
+    /// Whether the client advertised `capability` (e.g. `"sampling"`) in `initialize`.
+    pub fn client_supports(&self, capability: &str) -> bool {
+        self.peer.as_ref().is_some_and(|(_, caps)| caps.get(capability).is_some_and(|c| !c.is_null()))
+    }
+
+    /// Send a request to the client and wait up to `timeout` for its result.
+    pub async fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<T, PeerError> {
+        let (pending, _) = self.peer.as_ref().filter(|_| self.client_channel).ok_or(PeerError::NoChannel)?;
+        let result = peer::request(self, pending, method, params, timeout).await?;
+        serde_json::from_value(result).map_err(|e| PeerError::InvalidResponse(e.to_string()))
+    }
+
+    /// Ask the client's LLM for a message (`sampling/createMessage`).
+    pub async fn sample(&self, request: CreateMessageRequest) -> Result<CreateMessageResult, PeerError> {
+        if !self.client_supports("sampling") {
+            return Err(PeerError::Unsupported("sampling"));
+        }
+        let params = serde_json::to_value(request).unwrap();
+        self.request(CREATE_MESSAGE, params, DEFAULT_REQUEST_TIMEOUT).await
+    }
+
+    /// Ask the user to fill in `T` (`elicitation/create`), showing `message`.
+    pub async fn elicit<T: JsonSchema + DeserializeOwned>(
+        &self,
+        message: impl Into<String>,
+    ) -> Result<Elicitation<T>, PeerError> {
+        if !self.client_supports("elicitation") {
+            return Err(PeerError::Unsupported("elicitation"));
+        }
+        let params = serde_json::to_value(ElicitRequest::new::<T>(message)).unwrap();
+        let result: ElicitResult = self.request(ELICIT, params, ELICIT_TIMEOUT).await?;
+        Ok(match result.action {
+            ElicitAction::Accept => {
+                let content = Value::Object(result.content.unwrap_or_default());
+                let t = serde_json::from_value(content).map_err(|e| PeerError::InvalidResponse(e.to_string()))?;
+                Elicitation::Accept(t)
+            }
+            ElicitAction::Decline => Elicitation::Decline,
+            ElicitAction::Cancel => Elicitation::Cancel,
+        })
+    }
+
+    /// The client's roots, from the cache or else fetched with `roots/list`.
+    pub async fn roots(&self) -> Result<Vec<Root>, PeerError> {
+        if !self.client_supports("roots") {
+            return Err(PeerError::Unsupported("roots"));
+        }
+        let generation = match &self.roots {
+            Some(cache) => match cache.get() {
+                Some(roots) => return Ok(roots),
+                None => cache.generation(),
+            },
+            None => 0,
+        };
+        let result: ListRootsResult = self.request(LIST_ROOTS, json!({}), DEFAULT_REQUEST_TIMEOUT).await?;
+        if let Some(cache) = &self.roots {
+            cache.store(generation, result.roots.clone());
+        }
+        Ok(result.roots)
+    }
+
+    /// Whether `path` lies inside one of the client's roots; see [`roots::contains`].
+    pub async fn in_roots(&self, path: impl AsRef<std::path::Path>) -> Result<bool, PeerError> {
+        Ok(roots::contains(&self.roots().await?, path))
+    }
+
+    /// Fires when the client cancels the call or goes away. The handler future
+    /// is dropped at that point anyway; clone this into work that outlives it.
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// The `_meta.progressToken` the client sent with this call, if any.
+    pub fn progress_token(&self) -> Option<&Value> {
+        self.progress_token.as_ref()
+    }
+
+    /// Report how far along the call is with `notifications/progress`.
+    ///
+    /// `progress` should grow with every report. Does nothing unless the
+    /// client asked for progress by sending a token.
+    pub fn progress(&self, progress: f64, total: Option<f64>, message: Option<&str>) {
+        let Some(token) = &self.progress_token else {
+            return;
+        };
+        let mut params = json!({"progressToken": token, "progress": progress});
+        if let Some(total) = total {
+            params["total"] = json!(total);
+        }
+        if let Some(message) = message {
+            params["message"] = json!(message);
+        }
+        self.notify("notifications/progress", Some(params));
+    }
+
+    /// Send `notifications/message` to the caller if it asked for `level` or
+    /// less severe messages (`info` until it sets a level).
+    pub fn log(&self, level: LoggingLevel, data: impl Serialize) {
+        self.log_message(level, None, serde_json::to_value(data).unwrap_or(Value::Null));
+    }
+
+    pub(crate) fn log_message(&self, level: LoggingLevel, logger: Option<&str>, data: Value) {
+        if self.log_level.enabled(level) {
+            self.notify(LOG_MESSAGE, Some(logging::message_params(level, logger, data)));
+        }
+    }
+
+    /// Send a JSON-RPC notification to the caller.
+    ///
+    /// It travels on the response stream of the current request when there is
+    /// one, otherwise on the session's standalone stream. Without either the
+    /// message is dropped.
+    pub fn notify(&self, method: &str, params: Option<Value>) {
+        self.send(serde_json::to_value(Notification::new(method, params)).unwrap());
+    }
+
+    pub(crate) fn send(&self, message: Value) {
+        match (&self.response_stream, &self.session) {
+            (Some(tx), _) => {
+                let _ = tx.send(message);
+            }
+            (None, Some(session)) => {
+                session.outbox().push(&message);
+            }
+            (None, None) => {}
+        }
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -34,9 +373,111 @@ pub enum ToolError {
     InvalidArgs(String),
     #[error("internal: {0}")]
     Internal(String),
+    #[error("cancelled")]
+    Cancelled,
+}
+
+/// Execution failures reach the model as `isError` results; unknown tools, bad
+/// arguments and cancellation stay protocol errors.
+pub(crate) fn execution_error_as_result(
+    result: Result<CallToolResult, ToolError>,
+) -> Result<CallToolResult, ToolError> {
+    match result {
+        Err(ToolError::Internal(msg)) => Ok(CallToolResult::error(msg)),
+        other => other,
+    }
+}
+
+/// The result of `tools/call`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallToolResult {
+    pub content: Vec<ContentBlock>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+impl CallToolResult {
+    pub fn new(content: Vec<ContentBlock>) -> Self {
+        Self { content, ..Default::default() }
+    }
+
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::new(vec![ContentBlock::text(text)])
+    }
+
+    /// `value` as `structuredContent` plus, for older clients, a text block
+    /// holding it serialized. Structured tools send other values to clients
+    /// as `{"result": value}`.
+    pub fn structured(value: Value) -> Self {
+        Self { structured_content: Some(value.clone()), ..Self::text(value.to_string()) }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { is_error: true, ..Self::text(message) }
+    }
+}
+
+impl From<Value> for CallToolResult {
+    fn from(value: Value) -> Self {
+        Self::structured(value)
+    }
+}
+
+/// Return types of `#[mcp_tool]` functions.
+pub trait IntoToolResult {
+    fn into_tool_result(self) -> Result<CallToolResult, ToolError>;
+}
+
+impl IntoToolResult for CallToolResult {
+    fn into_tool_result(self) -> Result<CallToolResult, ToolError> {
+        Ok(self)
+    }
+}
+
+impl<T: Serialize> IntoToolResult for axum::Json<T> {
+    fn into_tool_result(self) -> Result<CallToolResult, ToolError> {
+        let value = serde_json::to_value(self.0).map_err(|e| ToolError::Internal(e.to_string()))?;
+        Ok(CallToolResult::structured(value))
+    }
+}
+
+impl IntoToolResult for String {
+    fn into_tool_result(self) -> Result<CallToolResult, ToolError> {
+        Ok(CallToolResult::text(self))
+    }
+}
+
+impl IntoToolResult for &'static str {
+    fn into_tool_result(self) -> Result<CallToolResult, ToolError> {
+        Ok(CallToolResult::text(self))
+    }
+}
+
+impl IntoToolResult for ContentBlock {
+    fn into_tool_result(self) -> Result<CallToolResult, ToolError> {
+        Ok(CallToolResult::new(vec![self]))
+    }
+}
+
+impl IntoToolResult for Vec<ContentBlock> {
+    fn into_tool_result(self) -> Result<CallToolResult, ToolError> {
+        Ok(CallToolResult::new(self))
+    }
+}
+
+impl<T: IntoToolResult, E: std::fmt::Display> IntoToolResult for Result<T, E> {
+    fn into_tool_result(self) -> Result<CallToolResult, ToolError> {
+        match self {
+            Ok(v) => v.into_tool_result(),
+            Err(e) => Err(ToolError::Internal(e.to_string())),
+        }
+    }
 }
 
 #[async_trait]
 pub trait ToolHandler: Send + Sync {
-    async fn call(&self, ctx: &ToolCtx, args: Value) -> Result<Value, ToolError>;
+    async fn call(&self, ctx: &ToolCtx, args: Value) -> Result<CallToolResult, ToolError>;
 }
@@ -0,0 +1,200 @@
+//! RFC 6570 URI templates, levels 1 and 2, used to match resource URIs.
+//!
+//! Supported expressions are `{var}` (simple: one path-safe run of unreserved
+//! or percent-encoded characters), `{+var}` (reserved: may span `/`, `?`, ...)
+//! and `{#var}` (fragment: a `#` followed by a reserved run). Matched values
+//! are percent-decoded.
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Simple(String),
+    Reserved(String),
+    Fragment(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UriTemplate {
+    source: String,
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid URI template {template:?}: {reason}")]
+pub struct InvalidTemplate {
+    pub template: String,
+    pub reason: &'static str,
+}
+
+impl UriTemplate {
+    pub fn parse(template: &str) -> Result<Self, InvalidTemplate> {
+        let err = |reason| InvalidTemplate { template: template.to_string(), reason };
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while !rest.is_empty() {
+            match rest.find('{') {
+                Some(0) => {
+                    let end = rest.find('}').ok_or_else(|| err("unclosed expression"))?;
+                    let expr = &rest[1..end];
+                    let (kind, name): (fn(String) -> Part, &str) = match expr.as_bytes().first() {
+                        Some(b'+') => (Part::Reserved, &expr[1..]),
+                        Some(b'#') => (Part::Fragment, &expr[1..]),
+                        _ => (Part::Simple, expr),
+                    };
+                    if name.is_empty()
+                        || !name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'.')
+                    {
+                        return Err(err("only single-variable level 1-2 expressions are supported"));
+                    }
+                    // A fragment starts at its `#`; anything else needs a literal in between.
+                    let after_expr = matches!(parts.last(), Some(p) if !matches!(p, Part::Literal(_)));
+                    if after_expr && !expr.starts_with('#') {
+                        return Err(err("adjacent expressions are ambiguous"));
+                    }
+                    parts.push(kind(name.to_string()));
+                    rest = &rest[end + 1..];
+                }
+                Some(start) => {
+                    if rest[..start].contains('}') {
+                        return Err(err("unopened expression"));
+                    }
+                    parts.push(Part::Literal(rest[..start].to_string()));
+                    rest = &rest[start..];
+                }
+                None => {
+                    if rest.contains('}') {
+                        return Err(err("unopened expression"));
+                    }
+                    parts.push(Part::Literal(rest.to_string()));
+                    rest = "";
+                }
+            }
+        }
+        Ok(Self { source: template.to_string(), parts })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// True when the template has no expressions, i.e. names a single URI.
+    pub fn is_literal(&self) -> bool {
+        self.parts.iter().all(|p| matches!(p, Part::Literal(_)))
+    }
+
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.parts.iter().filter_map(|p| match p {
+            Part::Literal(_) => None,
+            Part::Simple(n) | Part::Reserved(n) | Part::Fragment(n) => Some(n.as_str()),
+        })
+    }
+
+    /// Match a concrete URI, returning the decoded value of every variable.
+    pub fn matches(&self, uri: &str) -> Option<HashMap<String, String>> {
+        let mut vars = HashMap::new();
+        match_parts(&self.parts, uri, &mut vars).then_some(vars)
+    }
+}
+
+impl fmt::Display for UriTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn is_unreserved(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~' | '%')
+}
+
+fn is_reserved_or_unreserved(c: char) -> bool {
+    is_unreserved(c) || ":/?#[]@!$&'()*+,;=".contains(c)
+}
+
+fn match_parts(parts: &[Part], uri: &str, vars: &mut HashMap<String, String>) -> bool {
+    let Some((part, rest)) = parts.split_first() else {
+        return uri.is_empty();
+    };
+    let (name, allowed, uri): (&str, fn(char) -> bool, &str) = match part {
+        Part::Literal(lit) => {
+            return uri.strip_prefix(lit.as_str()).is_some_and(|u| match_parts(rest, u, vars));
+        }
+        Part::Simple(n) => (n, is_unreserved, uri),
+        Part::Reserved(n) => (n, is_reserved_or_unreserved, uri),
+        Part::Fragment(n) => match uri.strip_prefix('#') {
+            Some(u) => (n, is_reserved_or_unreserved, u),
+            None => return false,
+        },
+    };
+    // Longest run first, then backtrack so later literals can match.
+    let run = uri.find(|c| !allowed(c)).unwrap_or(uri.len());
+    let ends: Vec<usize> = uri[..run].char_indices().map(|(i, c)| i + c.len_utf8()).collect();
+    for &end in ends.iter().rev() {
+        if match_parts(rest, &uri[end..], vars) {
+            if let Some(value) = percent_decode(&uri[..end]) {
+                vars.insert(name.to_string(), value);
+                return true;
+            }
+            return false;
+        }
+    }
+    false
+}
+
+pub(crate) fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn simple_variables_stop_at_reserved_characters() {
+        let t = UriTemplate::parse("db://users/{id}/posts/{post}").unwrap();
+        assert_eq!(t.matches("db://users/42/posts/7"), Some(vars(&[("id", "42"), ("post", "7")])));
+        assert_eq!(t.matches("db://users/a%20b/posts/x"), Some(vars(&[("id", "a b"), ("post", "x")])));
+        assert_eq!(t.matches("db://users/4/2/posts/7"), None);
+        assert_eq!(t.matches("db://users//posts/7"), None);
+    }
+
+    #[test]
+    fn reserved_and_fragment_expansion() {
+        let t = UriTemplate::parse("file:///{+path}").unwrap();
+        assert_eq!(t.matches("file:///etc/app/config.toml"), Some(vars(&[("path", "etc/app/config.toml")])));
+
+        let t = UriTemplate::parse("docs://{+page}{#section}").unwrap();
+        assert_eq!(
+            t.matches("docs://guide/intro#setup"),
+            Some(vars(&[("page", "guide/intro"), ("section", "setup")]))
+        );
+        assert_eq!(t.matches("docs://guide/intro"), None);
+    }
+
+    #[test]
+    fn rejects_unsupported_templates() {
+        for bad in ["a://{x", "a://x}", "a://{}", "a://{x,y}", "a://{?q}", "a://{a}{b}"] {
+            assert!(UriTemplate::parse(bad).is_err(), "{bad}");
+        }
+        assert!(UriTemplate::parse("config://app").unwrap().is_literal());
+    }
+}
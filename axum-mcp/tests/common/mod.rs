@@ -0,0 +1,138 @@
+//! Helpers shared by the HTTP tests: requests against `handle_post`, and a
+//! client that reads server-to-client messages off its `GET` SSE stream.
+#![allow(dead_code)]
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::{Body, BodyDataStream};
+use axum::http::{HeaderValue, Request, StatusCode};
+use axum::response::Response;
+use axum_mcp::session::{Session, SessionManager};
+use axum_mcp::{McpLayerConfig, ToolRegistry};
+use futures::StreamExt;
+use serde_json::{Value, json};
+use tokio::sync::Mutex;
+
+pub fn initialize(capabilities: Value) -> Value {
+    json!({"jsonrpc":"2.0","id":0,"method":"initialize","params":{
+        "protocolVersion":"2025-06-18","capabilities":capabilities,"clientInfo":{"name":"t","version":"0"}}})
+}
+
+pub fn post(session: Option<&str>, body: Value) -> Request<Body> {
+    let mut builder = Request::post("/mcp");
+    if let Some(id) = session {
+        builder = builder.header("Mcp-Session-Id", HeaderValue::from_str(id).unwrap());
+    }
+    builder.body(Body::from(body.to_string())).unwrap()
+}
+
+pub fn sse_get(session: &str, last_event_id: Option<&str>) -> Request<Body> {
+    let mut builder = Request::get("/mcp")
+        .header("Accept", "text/event-stream")
+        .header("Mcp-Session-Id", session);
+    if let Some(id) = last_event_id {
+        builder = builder.header("Last-Event-ID", id);
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+/// `initialize` a session for a client without capabilities; returns its id.
+pub async fn open_session(reg: &Arc<ToolRegistry>, config: &McpLayerConfig, sessions: &SessionManager) -> String {
+    let resp = axum_mcp::http::handle_post(post(None, initialize(json!({}))), reg, config, sessions).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    resp.headers()["Mcp-Session-Id"].to_str().unwrap().to_string()
+}
+
+pub async fn body_json(resp: Response) -> Value {
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+/// Read the next complete SSE event (terminated by a blank line).
+pub async fn next_event(body: &mut BodyDataStream) -> Option<String> {
+    next_event_within(body, Duration::from_secs(2)).await.expect("timed out waiting for SSE event")
+}
+
+/// Like [`next_event`], but `Err` when nothing arrives within `timeout`.
+async fn next_event_within(body: &mut BodyDataStream, timeout: Duration) -> Result<Option<String>, ()> {
+    let mut buf = String::new();
+    while !buf.ends_with("\n\n") {
+        let Ok(chunk) = tokio::time::timeout(timeout, body.next()).await else {
+            return Err(());
+        };
+        let Some(chunk) = chunk else {
+            return Ok(None);
+        };
+        buf.push_str(std::str::from_utf8(&chunk.unwrap()).unwrap());
+    }
+    Ok(Some(buf))
+}
+
+/// The JSON-RPC message in an event's `data:` line, if it has one.
+pub fn event_message(event: &str) -> Option<Value> {
+    let data = event.lines().find_map(|l| l.strip_prefix("data:"))?;
+    Some(serde_json::from_str(data.trim_start()).unwrap())
+}
+
+/// A client holding one initialized session, with its `GET` stream open when
+/// SSE is on.
+pub struct Client {
+    pub reg: Arc<ToolRegistry>,
+    pub config: McpLayerConfig,
+    pub sessions: SessionManager,
+    pub sid: String,
+    events: Mutex<Option<BodyDataStream>>,
+}
+
+impl Client {
+    pub async fn connect(reg: Arc<ToolRegistry>, capabilities: Value) -> Self {
+        Self::connect_with(reg, capabilities, McpLayerConfig::default()).await
+    }
+
+    pub async fn connect_with(reg: Arc<ToolRegistry>, capabilities: Value, config: McpLayerConfig) -> Self {
+        let sessions = SessionManager::new(config.session_ttl);
+        let mut client = Client { reg, config, sessions, sid: String::new(), events: Mutex::new(None) };
+        let resp = client.post(initialize(capabilities)).await;
+        client.sid = resp.headers()["Mcp-Session-Id"].to_str().unwrap().to_string();
+        let initialized = json!({"jsonrpc":"2.0","method":"notifications/initialized"});
+        assert_eq!(client.post(initialized).await.status(), StatusCode::ACCEPTED);
+        if client.config.enable_sse {
+            let resp = axum_mcp::http::handle_sse_get(sse_get(&client.sid, None), &client.config, &client.sessions).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            *client.events.get_mut() = Some(resp.into_body().into_data_stream());
+        }
+        client
+    }
+
+    pub async fn post(&self, body: Value) -> Response {
+        let sid = (!self.sid.is_empty()).then_some(self.sid.as_str());
+        axum_mcp::http::handle_post(post(sid, body), &self.reg, &self.config, &self.sessions).await
+    }
+
+    pub fn session(&self) -> Arc<Session> {
+        self.sessions.get(&self.sid).unwrap()
+    }
+
+    pub async fn call(&self, tool: &str, args: Value) -> Value {
+        let call = json!({"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":tool,"arguments":args}});
+        body_json(self.post(call).await).await
+    }
+
+    /// The next message the server sent on the `GET` stream.
+    pub async fn next_outbound(&self) -> Value {
+        self.outbound_within(Duration::from_secs(2)).await.expect("timed out waiting for an outbound message")
+    }
+
+    /// The next message sent on the `GET` stream within `timeout`, if any.
+    pub async fn outbound_within(&self, timeout: Duration) -> Option<Value> {
+        let mut events = self.events.lock().await;
+        let body = events.as_mut().expect("no GET stream open");
+        loop {
+            // Skips the `retry` hint and keep-alive comments.
+            let event = next_event_within(body, timeout).await.ok()?.expect("GET stream ended");
+            if let Some(message) = event_message(&event) {
+                return Some(message);
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "http")]
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::Path;
+use axum::http::Request;
+use axum_mcp::completion::CompletionRequest;
+use axum_mcp::session::SessionManager;
+use axum_mcp::{McpLayerConfig, PromptRegistry, ResourceRegistry, ToolRegistry};
+use axum_mcp_macros::{mcp_prompt, mcp_resource};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Deserialize, JsonSchema)]
+enum Lang {
+    English,
+    Esperanto,
+    German,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct TranslateArgs {
+    to: Lang,
+    glossary: Option<String>,
+}
+
+async fn glossaries(req: CompletionRequest) -> Vec<String> {
+    let lang = req.context.get("to").cloned().unwrap_or_default();
+    vec![format!("{lang}-legal"), format!("{lang}-medical")]
+}
+
+#[mcp_prompt(name = "translate", complete(glossary = glossaries))]
+async fn translate(args: TranslateArgs) -> String {
+    let _ = (args.to, args.glossary);
+    "Translate this".into()
+}
+
+async fn file_ids(_req: CompletionRequest) -> Vec<String> {
+    (1..=120).map(|i| i.to_string()).collect()
+}
+
+#[mcp_resource(uri = "file:///files/{id}", complete(id = file_ids))]
+async fn get_file(Path(id): Path<u32>) -> String {
+    id.to_string()
+}
+
+async fn complete(params: Value) -> Value {
+    let state = Arc::new(());
+    let config = McpLayerConfig {
+        stateful: false,
+        prompts: Some(PromptRegistry::gather_with_state(state.clone())),
+        resources: Some(ResourceRegistry::gather_with_state(state.clone())),
+        ..Default::default()
+    };
+    let reg = ToolRegistry::empty_with_state(state);
+    let sessions = SessionManager::new(config.session_ttl);
+    let body = json!({"jsonrpc":"2.0","id":1,"method":"completion/complete","params":params});
+    let req = Request::post("/mcp").body(Body::from(body.to_string())).unwrap();
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn enum_arguments_complete_from_their_variants() {
+    let params = json!({"ref":{"type":"ref/prompt","name":"translate"},"argument":{"name":"to","value":"e"}});
+    assert_eq!(
+        complete(params).await["result"]["completion"],
+        json!({"values":["English","Esperanto","German"],"total":3,"hasMore":false})
+    );
+}
+
+#[tokio::test]
+async fn providers_see_the_other_arguments() {
+    let params = json!({"ref":{"type":"ref/prompt","name":"translate"},
+        "argument":{"name":"glossary","value":"med"},"context":{"arguments":{"to":"German"}}});
+    assert_eq!(complete(params).await["result"]["completion"]["values"], json!(["German-medical"]));
+
+    let params = json!({"ref":{"type":"ref/prompt","name":"nope"},"argument":{"name":"to","value":""}});
+    assert_eq!(complete(params).await["error"]["code"], -32602);
+}
+
+#[tokio::test]
+async fn template_variables_complete_with_paging_hints() {
+    let params = json!({"ref":{"type":"ref/resource","uri":"file:///files/{id}"},"argument":{"name":"id","value":"1"}});
+    let completion = complete(params).await["result"]["completion"].clone();
+    // 1, 10-19 and 100-120 start with "1"; 21, 31, ... 91 only contain it.
+    let values = completion["values"].as_array().unwrap();
+    assert_eq!(values[..3], [json!("1"), json!("10"), json!("11")]);
+    assert_eq!(values[32], "21");
+    assert_eq!(completion["total"], 40);
+    assert_eq!(completion["hasMore"], false);
+
+    let params = json!({"ref":{"type":"ref/resource","uri":"file:///files/{id}"},"argument":{"name":"id","value":""}});
+    let completion = complete(params).await["result"]["completion"].clone();
+    assert_eq!(completion["values"].as_array().unwrap().len(), 100);
+    assert_eq!((completion["total"].clone(), completion["hasMore"].clone()), (json!(120), json!(true)));
+}
@@ -0,0 +1,110 @@
+#![cfg(feature = "http")]
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use axum_mcp::cancel::CancellationToken;
+use axum_mcp::session::SessionManager;
+use axum_mcp::tool::{CallToolResult, ToolCtx, ToolDescriptor, ToolError, ToolHandler};
+use axum_mcp::{McpLayerConfig, ToolRegistry};
+use common::{open_session, post};
+use serde_json::{Value, json};
+
+/// Never finishes on its own; remembers its cancellation token.
+#[derive(Default)]
+struct Stuck {
+    tokens: Mutex<Vec<CancellationToken>>,
+}
+
+#[async_trait]
+impl ToolHandler for Stuck {
+    async fn call(&self, ctx: &ToolCtx, _args: Value) -> Result<CallToolResult, ToolError> {
+        self.tokens.lock().unwrap().push(ctx.cancellation().clone());
+        std::future::pending().await
+    }
+}
+
+async fn setup() -> (Arc<ToolRegistry>, Arc<Stuck>) {
+    let stuck = Arc::new(Stuck::default());
+    let reg = ToolRegistry::empty_with_state(Arc::new(()));
+    reg.insert(ToolDescriptor {
+        name: "stuck",
+        description: None,
+        input_schema: schemars::schema_for!(Value),
+        output_schema: schemars::schema_for!(Value),
+        handler: stuck.clone(),
+        structured: true,
+        title: None,
+        annotations: Default::default(),
+    })
+    .await;
+    (reg, stuck)
+}
+
+fn call(id: i64) -> Value {
+    json!({"jsonrpc":"2.0","id":id,"method":"tools/call","params":{"name":"stuck"}})
+}
+
+#[tokio::test]
+async fn cancelled_notification_aborts_the_call() {
+    let (reg, stuck) = setup().await;
+    let config = McpLayerConfig::default();
+    let sessions = Arc::new(SessionManager::new(config.session_ttl));
+    let sid = open_session(&reg, &config, &sessions).await;
+
+    let running = tokio::spawn({
+        let (reg, config, sessions, sid) = (reg.clone(), config.clone(), sessions.clone(), sid.clone());
+        async move { axum_mcp::http::handle_post(post(Some(&sid), call(5)), &reg, &config, &sessions).await }
+    });
+    let session = sessions.get(&sid).unwrap();
+    while session.in_flight().is_empty() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    let cancel = json!({"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":5}});
+    let resp = axum_mcp::http::handle_post(post(Some(&sid), cancel), &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+
+    // No JSON-RPC response is owed for a cancelled request.
+    let resp = tokio::time::timeout(Duration::from_secs(2), running).await.unwrap().unwrap();
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    assert!(stuck.tokens.lock().unwrap()[0].is_cancelled());
+    assert!(session.in_flight().is_empty());
+}
+
+#[tokio::test]
+async fn dropped_request_cancels_its_token() {
+    let (reg, stuck) = setup().await;
+    let config = McpLayerConfig::default();
+    let sessions = SessionManager::new(config.session_ttl);
+    let sid = open_session(&reg, &config, &sessions).await;
+
+    // What the server does when the client hangs up: drop the handler future.
+    let pending = axum_mcp::http::handle_post(post(Some(&sid), call(6)), &reg, &config, &sessions);
+    assert!(tokio::time::timeout(Duration::from_millis(50), pending).await.is_err());
+
+    assert!(stuck.tokens.lock().unwrap()[0].is_cancelled());
+    assert!(sessions.get(&sid).unwrap().in_flight().is_empty());
+}
+
+#[tokio::test]
+async fn deleting_the_session_cancels_running_calls() {
+    let (reg, stuck) = setup().await;
+    let config = McpLayerConfig::default();
+    let sessions = Arc::new(SessionManager::new(config.session_ttl));
+    let sid = open_session(&reg, &config, &sessions).await;
+
+    let running = tokio::spawn({
+        let (reg, config, sessions, sid) = (reg.clone(), config.clone(), sessions.clone(), sid.clone());
+        async move { axum_mcp::http::handle_post(post(Some(&sid), call(7)), &reg, &config, &sessions).await }
+    });
+    while stuck.tokens.lock().unwrap().is_empty() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    sessions.remove(&sid);
+    tokio::time::timeout(Duration::from_secs(2), running).await.unwrap().unwrap();
+    assert!(stuck.tokens.lock().unwrap()[0].is_cancelled());
+}
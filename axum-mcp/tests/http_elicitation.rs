@@ -0,0 +1,95 @@
+#![cfg(feature = "http")]
+mod common;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum_mcp::elicitation::Elicitation;
+use axum_mcp::tool::{CallToolResult, ToolCtx, ToolDescriptor, ToolError, ToolHandler};
+use axum_mcp::ToolRegistry;
+use common::Client;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Deserialize, JsonSchema)]
+struct Confirm {
+    /// Type the repository name to confirm
+    name: String,
+    keep_backup: Option<bool>,
+}
+
+struct Delete;
+#[async_trait]
+impl ToolHandler for Delete {
+    async fn call(&self, ctx: &ToolCtx, _args: Value) -> Result<CallToolResult, ToolError> {
+        Ok(match ctx.elicit::<Confirm>("Really delete?").await? {
+            Elicitation::Accept(c) => json!({"deleted": c.name, "backup": c.keep_backup.unwrap_or(true)}),
+            Elicitation::Decline => json!({"declined": true}),
+            Elicitation::Cancel => json!({"cancelled": true}),
+        }
+        .into())
+    }
+}
+
+async fn registry() -> Arc<ToolRegistry> {
+    let reg = ToolRegistry::empty_with_state(Arc::new(()));
+    reg.insert(ToolDescriptor {
+        name: "delete",
+        description: None,
+        input_schema: schemars::schema_for!(Value),
+        output_schema: schemars::schema_for!(Value),
+        handler: Arc::new(Delete),
+        structured: true,
+        title: None,
+        annotations: Default::default(),
+    })
+    .await;
+    reg
+}
+
+async fn answer(result: Value) -> Value {
+    let client = Arc::new(Client::connect(registry().await, json!({"elicitation":{}})).await);
+    let call = tokio::spawn({
+        let client = client.clone();
+        async move { client.call("delete", json!({})).await }
+    });
+
+    let req = client.next_outbound().await;
+    assert_eq!(req["method"], "elicitation/create");
+    assert_eq!(req["params"]["message"], "Really delete?");
+    let schema = &req["params"]["requestedSchema"];
+    assert_eq!(schema["required"], json!(["name"]));
+    assert_eq!(schema["properties"]["keep_backup"]["type"], "boolean");
+
+    let reply = json!({"jsonrpc":"2.0","id":req["id"],"result":result});
+    assert_eq!(client.post(reply).await.status(), 202);
+    call.await.unwrap()
+}
+
+#[tokio::test]
+async fn accepted_content_is_typed() {
+    let result = answer(json!({"action":"accept","content":{"name":"web","keep_backup":false}})).await;
+    assert_eq!(result["result"]["structuredContent"], json!({"deleted":"web","backup":false}));
+}
+
+#[tokio::test]
+async fn decline_and_cancel_carry_no_content() {
+    let result = answer(json!({"action":"decline"})).await;
+    assert_eq!(result["result"]["structuredContent"], json!({"declined":true}));
+    let result = answer(json!({"action":"cancel"})).await;
+    assert_eq!(result["result"]["structuredContent"], json!({"cancelled":true}));
+}
+
+#[tokio::test]
+async fn content_must_match_the_schema() {
+    let result = answer(json!({"action":"accept","content":{"keep_backup":true}})).await;
+    assert!(result["result"]["content"][0]["text"].as_str().unwrap().contains("invalid response"));
+}
+
+#[tokio::test]
+async fn elicitation_needs_the_client_capability() {
+    let client = Client::connect(registry().await, json!({"sampling":{}})).await;
+    let result = client.call("delete", json!({})).await;
+    assert!(result["result"]["content"][0]["text"].as_str().unwrap().contains("'elicitation' capability"));
+}
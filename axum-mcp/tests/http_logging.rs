@@ -0,0 +1,108 @@
+#![cfg(feature = "http")]
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::Request;
+use axum_mcp::logging::LoggingLevel;
+use axum_mcp::session::SessionManager;
+use axum_mcp::tool::{CallToolResult, ToolCtx, ToolDescriptor, ToolError, ToolHandler};
+use axum_mcp::{McpLayerConfig, ToolRegistry};
+use serde_json::{Value, json};
+
+struct Noisy;
+#[async_trait]
+impl ToolHandler for Noisy {
+    async fn call(&self, ctx: &ToolCtx, _args: Value) -> Result<CallToolResult, ToolError> {
+        ctx.log(LoggingLevel::Debug, "cache miss");
+        ctx.log(LoggingLevel::Info, json!({"rows": 3}));
+        ctx.log(LoggingLevel::Warning, "slow query");
+        #[cfg(feature = "trace")]
+        tracing::warn!(elapsed_ms = 1200, "from tracing");
+        Ok(json!({}).into())
+    }
+}
+
+async fn registry() -> Arc<ToolRegistry> {
+    let reg = ToolRegistry::empty_with_state(Arc::new(()));
+    reg.insert(ToolDescriptor {
+        name: "noisy",
+        description: None,
+        input_schema: schemars::schema_for!(Value),
+        output_schema: schemars::schema_for!(Value),
+        handler: Arc::new(Noisy),
+        structured: true,
+        title: None,
+        annotations: Default::default(),
+    })
+    .await;
+    reg
+}
+
+async fn post(reg: &Arc<ToolRegistry>, config: &McpLayerConfig, sessions: &SessionManager, sid: Option<&str>, body: Value) -> axum::response::Response {
+    let mut req = Request::post("/mcp");
+    if let Some(sid) = sid {
+        req = req.header("Mcp-Session-Id", sid);
+    }
+    axum_mcp::http::handle_post(req.body(Body::from(body.to_string())).unwrap(), reg, config, sessions).await
+}
+
+/// Call the tool in a fresh session and return what it sent to the session stream.
+async fn logged_with(level: Option<&str>) -> Vec<Value> {
+    let reg = registry().await;
+    let config = McpLayerConfig::default();
+    let sessions = SessionManager::new(config.session_ttl);
+    let init = json!({"jsonrpc":"2.0","id":0,"method":"initialize","params":{
+        "protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"t","version":"0"}}});
+    let resp = post(&reg, &config, &sessions, None, init).await;
+    let sid = resp.headers()["Mcp-Session-Id"].to_str().unwrap().to_string();
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let init: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(init["result"]["capabilities"]["logging"], json!({}));
+
+    if let Some(level) = level {
+        let set = json!({"jsonrpc":"2.0","id":1,"method":"logging/setLevel","params":{"level":level}});
+        post(&reg, &config, &sessions, Some(&sid), set).await;
+    }
+    let call = json!({"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"noisy"}});
+    post(&reg, &config, &sessions, Some(&sid), call).await;
+
+    let (queued, _) = sessions.get(&sid).unwrap().outbox().subscribe(None);
+    queued.iter().map(|e| serde_json::from_str(&e.data).unwrap()).collect()
+}
+
+fn levels(messages: &[Value]) -> Vec<&str> {
+    messages
+        .iter()
+        .filter(|m| m["method"] == "notifications/message" && m["params"]["logger"].is_null())
+        .map(|m| m["params"]["level"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn info_and_above_until_the_client_sets_a_level() {
+    let messages = logged_with(None).await;
+    assert_eq!(levels(&messages), ["info", "warning"]);
+    assert_eq!(messages[0]["params"]["data"], json!({"rows": 3}));
+
+    assert_eq!(levels(&logged_with(Some("debug")).await), ["debug", "info", "warning"]);
+    assert_eq!(levels(&logged_with(Some("warning")).await), ["warning"]);
+    assert!(levels(&logged_with(Some("emergency")).await).is_empty());
+}
+
+#[cfg(feature = "trace")]
+#[tokio::test]
+async fn tracing_events_in_a_tool_call_reach_the_client() {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let subscriber = tracing_subscriber::registry().with(axum_mcp::logging::McpLogLayer);
+    let _guard = tracing::subscriber::set_default(subscriber);
+    tracing::warn!("outside any tool call");
+
+    let messages = logged_with(Some("warning")).await;
+    let traced: Vec<_> = messages.iter().filter(|m| m["params"]["logger"].is_string()).collect();
+    assert_eq!(traced.len(), 1);
+    assert_eq!(traced[0]["params"]["level"], "warning");
+    assert_eq!(traced[0]["params"]["logger"], "http_logging");
+    assert_eq!(traced[0]["params"]["data"], json!({"message": "from tracing", "elapsed_ms": 1200}));
+}
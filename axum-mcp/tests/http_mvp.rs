@@ -3,20 +3,36 @@ use std::sync::Arc;
 
 use axum::http::{Request, HeaderValue};
 use axum::body::Body;
-use axum_mcp::{security::AllowedOrigins, ToolRegistry};
+use axum_mcp::{McpLayerConfig, ToolRegistry};
+use axum_mcp::session::SessionManager;
 use axum_mcp::tool::{ToolHandler, ToolCtx};
 use async_trait::async_trait;
 
 struct Echo;
 #[async_trait]
 impl ToolHandler for Echo {
-    async fn call(&self, _ctx: &ToolCtx, args: serde_json::Value) -> Result<serde_json::Value, axum_mcp::tool::ToolError> {
-        Ok(args)
+    async fn call(&self, _ctx: &ToolCtx, args: serde_json::Value) -> Result<axum_mcp::tool::CallToolResult, axum_mcp::tool::ToolError> {
+        Ok(args.into())
     }
 }
 
-#[tokio::test]
-async fn tools_list_and_call() {
+struct Shout;
+#[async_trait]
+impl ToolHandler for Shout {
+    async fn call(&self, _ctx: &ToolCtx, args: serde_json::Value) -> Result<axum_mcp::tool::CallToolResult, axum_mcp::tool::ToolError> {
+        Ok(axum_mcp::tool::CallToolResult::text(args.as_str().unwrap_or_default().to_uppercase()))
+    }
+}
+
+struct Fail;
+#[async_trait]
+impl ToolHandler for Fail {
+    async fn call(&self, _ctx: &ToolCtx, _args: serde_json::Value) -> Result<axum_mcp::tool::CallToolResult, axum_mcp::tool::ToolError> {
+        Err(axum_mcp::tool::ToolError::Internal("disk full".into()))
+    }
+}
+
+async fn echo_registry() -> Arc<ToolRegistry> {
     let reg = ToolRegistry::empty_with_state(Arc::new(()));
     reg.insert(axum_mcp::tool::ToolDescriptor {
         name: "echo",
@@ -25,35 +41,272 @@ async fn tools_list_and_call() {
         output_schema: schemars::schema_for!(serde_json::Value),
         handler: Arc::new(Echo),
         structured: true,
+        title: None,
+        annotations: Default::default(),
     }).await;
+    reg
+}
 
-    // list
-    let req = Request::post("/mcp")
+fn post(body: &'static str) -> Request<Body> {
+    Request::post("/mcp")
         .header("MCP-Protocol-Version", axum_mcp::security::REQUIRED_PROTOCOL_VERSION)
         .header("Origin", HeaderValue::from_static("http://127.0.0.1:3000"))
-        .body(Body::from("{\"op\":\"tools/list\"}"))
-        .unwrap();
-    let resp = axum_mcp::http::handle_post(
-        req,
-        &reg,
-        AllowedOrigins::LocalhostOnly,
-        axum_mcp::security::Auth::None,
-        axum_mcp::security::VersionPolicy::AllowFallback { required: axum_mcp::security::REQUIRED_PROTOCOL_VERSION, fallback: axum_mcp::security::FALLBACK_PROTOCOL_VERSION }
-    ).await;
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn post_in(session: &str, body: &'static str) -> Request<Body> {
+    let mut req = post(body);
+    req.headers_mut().insert("Mcp-Session-Id", HeaderValue::from_str(session).unwrap());
+    req
+}
+
+const INIT: &str = "{\"jsonrpc\":\"2.0\",\"id\":0,\"method\":\"initialize\",\"params\":{\"protocolVersion\":\"2025-03-26\",\"capabilities\":{},\"clientInfo\":{\"name\":\"t\",\"version\":\"0\"}}}";
+
+async fn body_json(resp: axum::response::Response) -> serde_json::Value {
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn tools_list_and_call() {
+    let reg = echo_registry().await;
+    let config = McpLayerConfig::default();
+    let sessions = SessionManager::new(config.session_ttl);
+
+    let resp = axum_mcp::http::handle_post(post(INIT), &reg, &config, &sessions).await;
+    let sid = resp.headers()["Mcp-Session-Id"].to_str().unwrap().to_string();
+
+    // list
+    let req = post_in(&sid, "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"tools/list\"}");
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
     assert_eq!(resp.status(), 200);
+    let body = body_json(resp).await;
+    assert_eq!(body["jsonrpc"], "2.0");
+    assert_eq!(body["id"], 1);
+    let echo = &body["result"]["tools"][0];
+    assert_eq!(echo["name"], "echo");
+    assert_eq!(echo["description"], "echo");
+    // `Value` has an untyped schema; tools/list always shows an object schema.
+    assert_eq!(echo["inputSchema"]["type"], "object");
+    assert_eq!(echo["outputSchema"]["type"], "object");
 
     // call
-    let req = Request::post("/mcp")
-        .header("MCP-Protocol-Version", axum_mcp::security::REQUIRED_PROTOCOL_VERSION)
-        .header("Origin", HeaderValue::from_static("http://127.0.0.1:3000"))
-        .body(Body::from("{\"op\":\"tools/call\",\"name\":\"echo\",\"args\":{\"x\":1}}"))
-        .unwrap();
-    let resp = axum_mcp::http::handle_post(
-        req,
-        &reg,
-        AllowedOrigins::LocalhostOnly,
-        axum_mcp::security::Auth::None,
-        axum_mcp::security::VersionPolicy::AllowFallback { required: axum_mcp::security::REQUIRED_PROTOCOL_VERSION, fallback: axum_mcp::security::FALLBACK_PROTOCOL_VERSION }
-    ).await;
+    let req = post_in(&sid, "{\"jsonrpc\":\"2.0\",\"id\":\"c\",\"method\":\"tools/call\",\"params\":{\"name\":\"echo\",\"arguments\":{\"x\":1}}}");
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), 200);
+    let body = body_json(resp).await;
+    assert_eq!(body["id"], "c");
+    assert_eq!(body["result"]["structuredContent"]["x"], 1);
+}
+
+#[tokio::test]
+async fn execution_failures_are_error_results() {
+    let reg = echo_registry().await;
+    reg.insert(axum_mcp::tool::ToolDescriptor {
+        name: "fail",
+        description: None,
+        input_schema: schemars::schema_for!(serde_json::Value),
+        output_schema: schemars::schema_for!(serde_json::Value),
+        handler: Arc::new(Fail),
+        structured: false,
+        title: None,
+        annotations: Default::default(),
+    }).await;
+    let config = McpLayerConfig::default();
+    let sessions = SessionManager::new(config.session_ttl);
+    let resp = axum_mcp::http::handle_post(post(INIT), &reg, &config, &sessions).await;
+    let sid = resp.headers()["Mcp-Session-Id"].to_str().unwrap().to_string();
+
+    let req = post_in(&sid, "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"tools/call\",\"params\":{\"name\":\"fail\"}}");
+    let body = body_json(axum_mcp::http::handle_post(req, &reg, &config, &sessions).await).await;
+    assert_eq!(body["result"]["isError"], true);
+    assert_eq!(body["result"]["content"][0]["text"], "disk full");
+
+    // Unknown tools are still protocol errors.
+    let req = post_in(&sid, "{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"tools/call\",\"params\":{\"name\":\"nope\"}}");
+    let body = body_json(axum_mcp::http::handle_post(req, &reg, &config, &sessions).await).await;
+    assert!(body["error"].is_object());
+}
+
+#[tokio::test]
+async fn unstructured_tools_answer_in_text_only() {
+    let reg = echo_registry().await;
+    reg.insert(axum_mcp::tool::ToolDescriptor {
+        name: "echo_text",
+        description: None,
+        input_schema: schemars::schema_for!(serde_json::Value),
+        output_schema: schemars::schema_for!(serde_json::Value),
+        handler: Arc::new(Echo),
+        structured: false,
+        title: None,
+        annotations: Default::default(),
+    }).await;
+    let config = McpLayerConfig::default();
+    let sessions = SessionManager::new(config.session_ttl);
+    let resp = axum_mcp::http::handle_post(post(INIT), &reg, &config, &sessions).await;
+    let sid = resp.headers()["Mcp-Session-Id"].to_str().unwrap().to_string();
+
+    let req = post_in(&sid, "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"tools/list\"}");
+    let body = body_json(axum_mcp::http::handle_post(req, &reg, &config, &sessions).await).await;
+    let tools = body["result"]["tools"].as_array().unwrap();
+    let by_name = |name: &str| tools.iter().find(|t| t["name"] == name).unwrap();
+    assert!(by_name("echo").get("outputSchema").is_some());
+    assert!(by_name("echo_text").get("outputSchema").is_none());
+
+    let req = post_in(&sid, "{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"tools/call\",\"params\":{\"name\":\"echo_text\",\"arguments\":{\"x\":1}}}");
+    let body = body_json(axum_mcp::http::handle_post(req, &reg, &config, &sessions).await).await;
+    assert!(body["result"].get("structuredContent").is_none());
+    assert_eq!(body["result"]["content"][0]["text"], "{\"x\":1}");
+}
+
+#[tokio::test]
+async fn legacy_op_bodies_behind_switch() {
+    let reg = echo_registry().await;
+    let config = McpLayerConfig { legacy_ops: true, ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+
+    let req = post("{\"op\":\"tools/call\",\"name\":\"echo\",\"args\":{\"x\":1}}");
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
     assert_eq!(resp.status(), 200);
+    let body = body_json(resp).await;
+    assert_eq!(body["ok"], true);
+    assert_eq!(body["result"]["x"], 1);
+
+    // The legacy tool list keeps its own field names.
+    let req = post("{\"op\":\"tools/list\"}");
+    let body = body_json(axum_mcp::http::handle_post(req, &reg, &config, &sessions).await).await;
+    let echo = &body["tools"][0];
+    assert_eq!(echo["name"], "echo");
+    assert_eq!(echo["desc"], "echo");
+    assert_eq!(echo["structuredContent"], true);
+    assert!(echo.get("input_schema").is_some() && echo.get("output_schema").is_some());
+    assert!(echo.get("inputSchema").is_none());
+
+    // Raw values come back as returned, whatever their shape or the tool's.
+    let req = post("{\"op\":\"tools/call\",\"name\":\"echo\",\"args\":[1,2]}");
+    let body = body_json(axum_mcp::http::handle_post(req, &reg, &config, &sessions).await).await;
+    assert_eq!(body["result"], serde_json::json!([1, 2]));
+    reg.insert(axum_mcp::tool::ToolDescriptor {
+        name: "shout",
+        description: None,
+        input_schema: schemars::schema_for!(serde_json::Value),
+        output_schema: schemars::schema_for!(serde_json::Value),
+        handler: Arc::new(Shout),
+        structured: false,
+        title: None,
+        annotations: Default::default(),
+    }).await;
+    let req = post("{\"op\":\"tools/call\",\"name\":\"shout\",\"args\":\"hi\"}");
+    let body = body_json(axum_mcp::http::handle_post(req, &reg, &config, &sessions).await).await;
+    assert_eq!(body, serde_json::json!({"ok": true, "result": "HI"}));
+
+    // Without the switch the same body is not a valid JSON-RPC message.
+    let req = post("{\"op\":\"tools/list\"}");
+    let resp = axum_mcp::http::handle_post(req, &reg, &McpLayerConfig::default(), &sessions).await;
+    assert_eq!(resp.status(), 400);
+    assert_eq!(body_json(resp).await["error"]["code"], -32600);
+}
+
+#[tokio::test]
+async fn initialize_reports_server_info() {
+    let reg = echo_registry().await;
+    let config = McpLayerConfig {
+        server_info: axum_mcp::ServerInfo {
+            name: "demo".into(),
+            version: "1.2.3".into(),
+            instructions: Some("call echo".into()),
+        },
+        ..Default::default()
+    };
+    let sessions = SessionManager::new(config.session_ttl);
+    let resp = axum_mcp::http::handle_post(post(INIT), &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), 200);
+    let sid = resp.headers()["Mcp-Session-Id"].to_str().unwrap().to_string();
+    let result = body_json(resp).await["result"].clone();
+    assert_eq!(result["protocolVersion"], "2025-03-26");
+    assert_eq!(result["serverInfo"]["name"], "demo");
+    assert_eq!(result["serverInfo"]["version"], "1.2.3");
+    assert_eq!(result["instructions"], "call echo");
+    assert!(result["capabilities"]["tools"].is_object());
+
+    let req = post_in(&sid, "{\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\"}");
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), 202);
+}
+
+#[tokio::test]
+async fn batches_and_notifications() {
+    let reg = echo_registry().await;
+    let config = McpLayerConfig { max_batch_size: 3, ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+    let resp = axum_mcp::http::handle_post(post(INIT), &reg, &config, &sessions).await;
+    let sid = resp.headers()["Mcp-Session-Id"].to_str().unwrap().to_string();
+
+    // Only notifications: accepted, no body.
+    let req = post_in(&sid, "[{\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\"}]");
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), 202);
+    assert!(axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap().is_empty());
+
+    let req = post_in(&sid, "[{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"tools/call\",\"params\":{\"name\":\"echo\",\"arguments\":{\"x\":1}}},\
+        {\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\"},\
+        {\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"ping\"}]");
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), 200);
+    let body = body_json(resp).await;
+    let replies = body.as_array().unwrap();
+    assert_eq!(replies.len(), 2);
+    let by_id = |id: i64| replies.iter().find(|r| r["id"] == id).unwrap().clone();
+    assert_eq!(by_id(1)["result"]["structuredContent"]["x"], 1);
+    assert!(by_id(2)["result"].is_object());
+
+    // Malformed members get their own error; the rest still run.
+    let req = post_in(&sid, "[{\"jsonrpc\":\"1.0\",\"id\":3,\"method\":\"ping\"},{\"jsonrpc\":\"2.0\",\"id\":4,\"method\":\"ping\"}]");
+    let body = body_json(axum_mcp::http::handle_post(req, &reg, &config, &sessions).await).await;
+    assert_eq!(body.as_array().unwrap().len(), 2);
+    assert_eq!(body[0]["error"]["code"], -32600);
+
+    for oversized in ["[]", "[1,2,3,4]"] {
+        let resp = axum_mcp::http::handle_post(post_in(&sid, oversized), &reg, &config, &sessions).await;
+        assert_eq!(resp.status(), 400);
+        assert_eq!(body_json(resp).await["error"]["code"], -32600);
+    }
+}
+
+#[tokio::test]
+async fn tools_list_pages_by_name() {
+    let reg = echo_registry().await;
+    for name in ["delta", "alpha", "charlie", "bravo"] {
+        let mut d = reg.get("echo").await.unwrap();
+        d.name = name;
+        reg.insert(d).await;
+    }
+    let config = McpLayerConfig { stateful: false, page_size: 2, ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+    let list = |cursor: Option<&str>| {
+        let mut body = serde_json::json!({"jsonrpc":"2.0","id":1,"method":"tools/list"});
+        if let Some(c) = cursor {
+            body["params"] = serde_json::json!({"cursor": c});
+        }
+        Request::post("/mcp").body(Body::from(body.to_string())).unwrap()
+    };
+
+    let mut names = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let resp = axum_mcp::http::handle_post(list(cursor.as_deref()), &reg, &config, &sessions).await;
+        let result = body_json(resp).await["result"].clone();
+        let page: Vec<String> = result["tools"].as_array().unwrap().iter().map(|t| t["name"].as_str().unwrap().to_string()).collect();
+        assert!(page.len() <= 2);
+        names.extend(page);
+        match result["nextCursor"].as_str() {
+            Some(next) => cursor = Some(next.to_string()),
+            None => break,
+        }
+    }
+    assert_eq!(names, ["alpha", "bravo", "charlie", "delta", "echo"]);
+
+    let resp = axum_mcp::http::handle_post(list(Some("not-a-cursor")), &reg, &config, &sessions).await;
+    assert_eq!(body_json(resp).await["error"]["code"], -32602);
 }
@@ -0,0 +1,151 @@
+#![cfg(feature = "http")]
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::Request;
+use axum_mcp::resource::{ResourceContents, ResourceDescriptor, ResourceError, ResourceHandler, UriVars};
+use axum_mcp::session::SessionManager;
+use axum_mcp::tool::ToolCtx;
+use axum_mcp::{McpLayerConfig, ResourceRegistry, ToolRegistry};
+use serde_json::{Value, json};
+
+struct Rows;
+#[async_trait]
+impl ResourceHandler for Rows {
+    async fn read(&self, _ctx: &ToolCtx, uri: &str, vars: &UriVars) -> Result<Vec<ResourceContents>, ResourceError> {
+        match vars.get("id").map(String::as_str) {
+            Some("404") => Err(ResourceError::NotFound(uri.to_string())),
+            Some(id) => Ok(vec![ResourceContents::text(uri, json!({"id": id}).to_string()).with_mime_type("application/json")]),
+            None => Ok(vec![ResourceContents::blob(uri, b"\x00\x01").with_mime_type("application/octet-stream")]),
+        }
+    }
+}
+
+async fn config() -> McpLayerConfig {
+    let resources = ResourceRegistry::empty_with_state(Arc::new(()));
+    for (uri, name) in [("db://rows/{id}", "row"), ("db://dump", "dump")] {
+        let desc = ResourceDescriptor {
+            uri: uri.into(),
+            name: name.into(),
+            description: None,
+            mime_type: None,
+            handler: Arc::new(Rows),
+            completions: Default::default(),
+        };
+        resources.insert(desc).await.unwrap();
+    }
+    McpLayerConfig { stateful: false, resources: Some(resources), ..Default::default() }
+}
+
+async fn rpc(config: &McpLayerConfig, method: &str, params: Value) -> Value {
+    let sessions = SessionManager::new(config.session_ttl);
+    rpc_in(config, &sessions, None, method, params).await
+}
+
+async fn rpc_in(
+    config: &McpLayerConfig,
+    sessions: &SessionManager,
+    session: Option<&str>,
+    method: &str,
+    params: Value,
+) -> Value {
+    let reg = ToolRegistry::empty_with_state(Arc::new(()));
+    let body = json!({"jsonrpc":"2.0","id":1,"method":method,"params":params});
+    let mut req = Request::post("/mcp");
+    if let Some(sid) = session {
+        req = req.header("Mcp-Session-Id", sid);
+    }
+    let resp = axum_mcp::http::handle_post(req.body(Body::from(body.to_string())).unwrap(), &reg, config, sessions).await;
+    let sid = resp.headers().get("Mcp-Session-Id").map(|v| v.to_str().unwrap().to_string());
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let mut reply: Value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    if let Some(sid) = sid {
+        reply["sessionId"] = json!(sid);
+    }
+    reply
+}
+
+#[tokio::test]
+async fn lists_resources_and_templates() {
+    let config = config().await;
+    let init = rpc(&config, "initialize", json!({"protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"t","version":"0"}})).await;
+    assert!(init["result"]["capabilities"]["resources"].is_object());
+
+    let list = rpc(&config, "resources/list", json!({})).await;
+    assert_eq!(list["result"]["resources"], json!([{"uri":"db://dump","name":"dump"}]));
+    let templates = rpc(&config, "resources/templates/list", json!({})).await;
+    assert_eq!(templates["result"]["resourceTemplates"], json!([{"uriTemplate":"db://rows/{id}","name":"row"}]));
+}
+
+#[tokio::test]
+async fn stateless_servers_promise_no_push_notifications() {
+    let config = config().await;
+    let init = rpc(&config, "initialize", json!({"protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"t","version":"0"}})).await;
+    let capabilities = &init["result"]["capabilities"];
+    assert_eq!(capabilities["tools"], json!({}));
+    assert_eq!(capabilities["resources"], json!({}));
+}
+
+#[tokio::test]
+async fn reads_text_blob_and_missing() {
+    let config = config().await;
+    let row = rpc(&config, "resources/read", json!({"uri":"db://rows/7"})).await;
+    assert_eq!(row["result"]["contents"], json!([{"uri":"db://rows/7","mimeType":"application/json","text":"{\"id\":\"7\"}"}]));
+
+    let dump = rpc(&config, "resources/read", json!({"uri":"db://dump"})).await;
+    assert_eq!(dump["result"]["contents"][0]["blob"], "AAE=");
+
+    for uri in ["db://rows/404", "db://nothing"] {
+        let missing = rpc(&config, "resources/read", json!({"uri": uri})).await;
+        assert_eq!(missing["error"]["code"], -32002);
+        assert_eq!(missing["error"]["data"]["uri"], uri);
+    }
+
+    let without = McpLayerConfig { resources: None, ..config };
+    assert_eq!(rpc(&without, "resources/list", json!({})).await["error"]["code"], -32601);
+}
+
+#[tokio::test]
+async fn subscribers_hear_about_updates() {
+    let config = McpLayerConfig { stateful: true, ..config().await };
+    let sessions = SessionManager::new(config.session_ttl);
+    let init = json!({"protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"t","version":"0"}});
+    let init = rpc_in(&config, &sessions, None, "initialize", init).await;
+    assert_eq!(init["result"]["capabilities"]["resources"]["subscribe"], true);
+    let sid = init["sessionId"].as_str().unwrap().to_string();
+    let session = sessions.get(&sid).unwrap();
+    let call = |method: &'static str, uri: &'static str| rpc_in(&config, &sessions, Some(&sid), method, json!({"uri": uri}));
+
+    assert_eq!(call("resources/subscribe", "db://rows/7").await["result"], json!({}));
+    assert_eq!(call("resources/subscribe", "db://dump").await["result"], json!({}));
+    assert_eq!(call("resources/subscribe", "db://nothing").await["error"]["code"], -32002);
+    assert_eq!(call("resources/unsubscribe", "db://dump").await["result"], json!({}));
+
+    let notifier = config.resources.as_ref().unwrap().notifier();
+    for uri in ["db://dump", "db://rows/8", "db://rows/7"] {
+        notifier.updated(uri);
+    }
+    let queued = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            let (queued, _) = session.outbox().subscribe(None);
+            if !queued.is_empty() {
+                break queued;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(queued.len(), 1);
+    let msg: Value = serde_json::from_str(&queued[0].data).unwrap();
+    assert_eq!(msg, json!({"jsonrpc":"2.0","method":"notifications/resources/updated","params":{"uri":"db://rows/7"}}));
+
+    sessions.remove(&sid);
+    assert!(!session.subscriptions().contains("db://rows/7"));
+
+    // Without sessions there is nobody to notify.
+    let stateless = McpLayerConfig { stateful: false, ..config };
+    assert_eq!(rpc(&stateless, "resources/subscribe", json!({"uri":"db://dump"})).await["error"]["code"], -32600);
+}
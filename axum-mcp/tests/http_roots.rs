@@ -0,0 +1,107 @@
+#![cfg(feature = "http")]
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum_mcp::tool::{CallToolResult, ToolCtx, ToolDescriptor, ToolError, ToolHandler};
+use axum_mcp::ToolRegistry;
+use common::Client;
+use serde_json::{Value, json};
+
+struct Open;
+#[async_trait]
+impl ToolHandler for Open {
+    async fn call(&self, ctx: &ToolCtx, args: Value) -> Result<CallToolResult, ToolError> {
+        let path = args["path"].as_str().unwrap_or_default();
+        Ok(json!({"allowed": ctx.in_roots(path).await?}).into())
+    }
+}
+
+async fn registry() -> Arc<ToolRegistry> {
+    let reg = ToolRegistry::empty_with_state(Arc::new(()));
+    reg.insert(ToolDescriptor {
+        name: "open",
+        description: None,
+        input_schema: schemars::schema_for!(Value),
+        output_schema: schemars::schema_for!(Value),
+        handler: Arc::new(Open),
+        structured: true,
+        title: None,
+        annotations: Default::default(),
+    })
+    .await;
+    reg
+}
+
+/// Answer the next message on the stream, which must be `roots/list`.
+async fn answer_roots(client: &Client, uris: &[&str]) {
+    let req = client.next_outbound().await;
+    assert_eq!(req["method"], "roots/list");
+    let roots: Vec<Value> = uris.iter().map(|u| json!({"uri": u})).collect();
+    let reply = json!({"jsonrpc":"2.0","id":req["id"],"result":{"roots":roots}});
+    assert_eq!(client.post(reply).await.status(), 202);
+}
+
+/// Wait until the session holds the roots fetched in the background.
+async fn roots_cached(client: &Client) {
+    let session = client.session();
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while session.roots().get().is_none() {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn roots_are_fetched_once_the_client_is_initialized() {
+    let client = Client::connect(registry().await, json!({"roots":{"listChanged":true}})).await;
+    answer_roots(&client, &["file:///work/app"]).await;
+    roots_cached(&client).await;
+
+    // Tools read the cache: nothing new goes out.
+    let allowed = client.call("open", json!({"path": "/work/app/src/main.rs"})).await;
+    assert_eq!(allowed["result"]["structuredContent"]["allowed"], true);
+    let denied = client.call("open", json!({"path": "/etc/passwd"})).await;
+    assert_eq!(denied["result"]["structuredContent"]["allowed"], false);
+    assert!(client.outbound_within(Duration::from_millis(50)).await.is_none());
+}
+
+#[tokio::test]
+async fn list_changed_fetches_the_roots_again() {
+    let client = Client::connect(registry().await, json!({"roots":{"listChanged":true}})).await;
+    answer_roots(&client, &["file:///work/app"]).await;
+    roots_cached(&client).await;
+
+    let changed = json!({"jsonrpc":"2.0","method":"notifications/roots/list_changed"});
+    assert_eq!(client.post(changed).await.status(), 202);
+    assert!(client.session().roots().get().is_none());
+    answer_roots(&client, &["file:///work/app", "file:///etc"]).await;
+    roots_cached(&client).await;
+    let allowed = client.call("open", json!({"path": "/etc/passwd"})).await;
+    assert_eq!(allowed["result"]["structuredContent"]["allowed"], true);
+}
+
+#[tokio::test]
+async fn tools_fetch_roots_while_none_are_cached() {
+    let client = Arc::new(Client::connect(registry().await, json!({"roots":{}})).await);
+    // The client never answers the fetch sent after `initialized`.
+    assert_eq!(client.next_outbound().await["method"], "roots/list");
+
+    let open = tokio::spawn({
+        let client = client.clone();
+        async move { client.call("open", json!({"path": "/work/app/Cargo.toml"})).await }
+    });
+    answer_roots(&client, &["file:///work/app"]).await;
+    assert_eq!(open.await.unwrap()["result"]["structuredContent"]["allowed"], true);
+}
+
+#[tokio::test]
+async fn roots_need_the_client_capability() {
+    let client = Client::connect(registry().await, json!({})).await;
+    let result = client.call("open", json!({"path": "/"})).await;
+    assert!(result["result"]["content"][0]["text"].as_str().unwrap().contains("'roots' capability"));
+}
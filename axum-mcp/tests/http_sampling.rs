@@ -0,0 +1,97 @@
+#![cfg(feature = "http")]
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum_mcp::sampling::{CreateMessageRequest, SamplingMessage};
+use axum_mcp::tool::{CallToolResult, ToolCtx, ToolDescriptor, ToolError, ToolHandler};
+use axum_mcp::{McpLayerConfig, ToolRegistry};
+use common::Client;
+use serde_json::{Value, json};
+
+struct Summarize;
+#[async_trait]
+impl ToolHandler for Summarize {
+    async fn call(&self, ctx: &ToolCtx, args: Value) -> Result<CallToolResult, ToolError> {
+        if args["quick"] == true {
+            let v: Value = ctx.request("ping", json!({}), Duration::from_millis(20)).await?;
+            return Ok(v.into());
+        }
+        let req = CreateMessageRequest::new(vec![SamplingMessage::user("Summarize: 1, 2, 3")], 50)
+            .with_system_prompt("Be brief");
+        let reply = ctx.sample(req).await?;
+        Ok(json!({"summary": reply.text(), "model": reply.model}).into())
+    }
+}
+
+async fn registry() -> Arc<ToolRegistry> {
+    let reg = ToolRegistry::empty_with_state(Arc::new(()));
+    reg.insert(ToolDescriptor {
+        name: "summarize",
+        description: None,
+        input_schema: schemars::schema_for!(Value),
+        output_schema: schemars::schema_for!(Value),
+        handler: Arc::new(Summarize),
+        structured: true,
+        title: None,
+        annotations: Default::default(),
+    })
+    .await;
+    reg
+}
+
+#[tokio::test]
+async fn sample_round_trips_through_the_session_stream() {
+    let client = Arc::new(Client::connect(registry().await, json!({"sampling":{}})).await);
+    let call = tokio::spawn({
+        let client = client.clone();
+        async move { client.call("summarize", json!({})).await }
+    });
+
+    let req = client.next_outbound().await;
+    assert_eq!(req["method"], "sampling/createMessage");
+    assert_eq!(req["params"]["systemPrompt"], "Be brief");
+    assert_eq!(req["params"]["maxTokens"], 50);
+    assert_eq!(req["params"]["messages"][0], json!({"role":"user","content":{"type":"text","text":"Summarize: 1, 2, 3"}}));
+
+    let answer = json!({"jsonrpc":"2.0","id":req["id"],"result":{
+        "role":"assistant","content":{"type":"text","text":"Small numbers."},"model":"m-1","stopReason":"endTurn"}});
+    assert_eq!(client.post(answer).await.status(), 202);
+
+    let result = call.await.unwrap();
+    assert_eq!(result["result"]["structuredContent"], json!({"summary":"Small numbers.","model":"m-1"}));
+    assert!(client.session().pending_requests().is_empty());
+}
+
+#[tokio::test]
+async fn sampling_needs_the_client_capability() {
+    let client = Client::connect(registry().await, json!({})).await;
+    let result = client.call("summarize", json!({})).await;
+    assert_eq!(result["result"]["isError"], true);
+    assert!(result["result"]["content"][0]["text"].as_str().unwrap().contains("'sampling' capability"));
+}
+
+#[tokio::test]
+async fn unanswered_requests_time_out_and_are_cancelled() {
+    let client = Client::connect(registry().await, json!({})).await;
+    let result = client.call("summarize", json!({"quick": true})).await;
+    assert!(result["result"]["content"][0]["text"].as_str().unwrap().contains("did not answer"));
+
+    let req = client.next_outbound().await;
+    let cancel = client.next_outbound().await;
+    assert_eq!(cancel["method"], "notifications/cancelled");
+    assert_eq!(cancel["params"]["requestId"], req["id"]);
+    assert!(client.session().pending_requests().is_empty());
+}
+
+#[tokio::test]
+async fn without_a_get_stream_requests_fail_fast() {
+    let config = McpLayerConfig { enable_sse: false, ..Default::default() };
+    let client = Client::connect_with(registry().await, json!({"sampling":{}}), config).await;
+    let result = tokio::time::timeout(Duration::from_secs(2), client.call("summarize", json!({}))).await.unwrap();
+    assert_eq!(result["result"]["isError"], true);
+    assert!(result["result"]["content"][0]["text"].as_str().unwrap().contains("no channel"));
+    assert!(client.session().pending_requests().is_empty());
+}
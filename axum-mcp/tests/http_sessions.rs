@@ -0,0 +1,125 @@
+#![cfg(feature = "http")]
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::{HeaderValue, Request, StatusCode};
+use axum_mcp::session::SessionManager;
+use axum_mcp::tool::{CallToolResult, ToolCtx, ToolDescriptor, ToolError, ToolHandler};
+use axum_mcp::{McpLayerConfig, ToolRegistry};
+use common::{body_json, event_message, next_event, open_session, post, sse_get};
+use serde_json::{Value, json};
+
+struct WhoAmI;
+#[async_trait]
+impl ToolHandler for WhoAmI {
+    async fn call(&self, ctx: &ToolCtx, _args: Value) -> Result<CallToolResult, ToolError> {
+        let session = ctx.session.as_ref().ok_or_else(|| ToolError::Internal("no session".into()))?;
+        Ok(json!({"session": session.id()}).into())
+    }
+}
+
+async fn registry() -> Arc<ToolRegistry> {
+    let reg = ToolRegistry::empty_with_state(Arc::new(()));
+    reg.insert(ToolDescriptor {
+        name: "whoami",
+        description: None,
+        input_schema: schemars::schema_for!(Value),
+        output_schema: schemars::schema_for!(Value),
+        handler: Arc::new(WhoAmI),
+        structured: true,
+        title: None,
+        annotations: Default::default(),
+    })
+    .await;
+    reg
+}
+
+#[tokio::test]
+async fn session_required_after_initialize() {
+    let reg = registry().await;
+    let config = McpLayerConfig::default();
+    let sessions = SessionManager::new(config.session_ttl);
+    let list = json!({"jsonrpc":"2.0","id":1,"method":"tools/list"});
+
+    let resp = axum_mcp::http::handle_post(post(None, list.clone()), &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = axum_mcp::http::handle_post(post(Some("bogus"), list.clone()), &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let sid = open_session(&reg, &config, &sessions).await;
+    assert_eq!(sessions.len(), 1);
+    let resp = axum_mcp::http::handle_post(post(Some(&sid), list), &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(body_json(resp).await["result"]["tools"].is_array());
+}
+
+#[tokio::test]
+async fn tools_see_their_session() {
+    let reg = registry().await;
+    let config = McpLayerConfig::default();
+    let sessions = SessionManager::new(config.session_ttl);
+    let sid = open_session(&reg, &config, &sessions).await;
+
+    let call = json!({"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"whoami"}});
+    let resp = axum_mcp::http::handle_post(post(Some(&sid), call), &reg, &config, &sessions).await;
+    assert_eq!(body_json(resp).await["result"]["structuredContent"]["session"], sid.as_str());
+}
+
+#[tokio::test]
+async fn delete_terminates_session() {
+    let reg = registry().await;
+    let config = McpLayerConfig::default();
+    let sessions = SessionManager::new(config.session_ttl);
+    let sid = open_session(&reg, &config, &sessions).await;
+
+    let delete = || {
+        Request::delete("/mcp")
+            .header("Mcp-Session-Id", HeaderValue::from_str(&sid).unwrap())
+            .body(Body::empty())
+            .unwrap()
+    };
+    let resp = axum_mcp::http::handle_delete(delete(), &config, &sessions).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    let resp = axum_mcp::http::handle_delete(delete(), &config, &sessions).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let ping = json!({"jsonrpc":"2.0","id":3,"method":"ping"});
+    let resp = axum_mcp::http::handle_post(post(Some(&sid), ping), &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn idle_sessions_expire() {
+    let reg = registry().await;
+    let config = McpLayerConfig { session_ttl: Duration::from_millis(20), ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+    let sid = open_session(&reg, &config, &sessions).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let ping = json!({"jsonrpc":"2.0","id":1,"method":"ping"});
+    let resp = axum_mcp::http::handle_post(post(Some(&sid), ping), &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn registry_changes_reach_open_sessions() {
+    let reg = registry().await;
+    let config = McpLayerConfig::default();
+    let sessions = SessionManager::new(config.session_ttl);
+    let sid = open_session(&reg, &config, &sessions).await;
+    let config = McpLayerConfig { sse_retry: None, ..config };
+    let resp = axum_mcp::http::handle_sse_get(sse_get(&sid, None), &config, &sessions).await;
+    let mut events = resp.into_body().into_data_stream();
+
+    let mut desc = reg.get("whoami").await.unwrap();
+    desc.name = "whoami2";
+    reg.insert(desc).await;
+
+    let msg = event_message(&next_event(&mut events).await.unwrap()).unwrap();
+    assert_eq!(msg["method"], "notifications/tools/list_changed");
+}
@@ -1,15 +1,22 @@
 #![cfg(feature = "http")]
 #![allow(unused_imports, dead_code)]
+mod common;
+
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use axum::body::Body;
 use axum::http::{HeaderValue, Method, Request};
+use futures::StreamExt;
+use tower::{Layer, ServiceExt};
+use axum_mcp::session::SessionManager;
 use axum_mcp::tool::{ToolCtx, ToolHandler};
 use axum_mcp::{
-    ToolRegistry,
+    McpLayer, McpLayerConfig, ToolRegistry,
     security::{AllowedOrigins, Auth},
 };
+use common::{next_event, open_session, sse_get};
 
 struct Echo;
 #[async_trait]
@@ -18,8 +25,8 @@ impl ToolHandler for Echo {
         &self,
         _ctx: &ToolCtx,
         args: serde_json::Value,
-    ) -> Result<serde_json::Value, axum_mcp::tool::ToolError> {
-        Ok(args)
+    ) -> Result<axum_mcp::tool::CallToolResult, axum_mcp::tool::ToolError> {
+        Ok(args.into())
     }
 }
 
@@ -32,29 +39,30 @@ async fn auth_rejects_without_bearer() {
             axum_mcp::security::REQUIRED_PROTOCOL_VERSION,
         )
         .header("Origin", HeaderValue::from_static("http://127.0.0.1:3000"))
-        .body(Body::from("{\"op\":\"tools/list\"}"))
+        .body(Body::from(
+            "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"tools/list\"}",
+        ))
         .unwrap();
-    let resp = axum_mcp::http::handle_post(
-        req,
-        &reg,
-        AllowedOrigins::LocalhostOnly,
-        Auth::Bearer {
+    let config = McpLayerConfig {
+        allowed_origins: AllowedOrigins::LocalhostOnly,
+        auth: Auth::Bearer {
             token: "secret".into(),
         },
-        axum_mcp::security::VersionPolicy::AllowFallback {
-            required: axum_mcp::security::REQUIRED_PROTOCOL_VERSION,
-            fallback: axum_mcp::security::FALLBACK_PROTOCOL_VERSION,
-        },
-    )
-    .await;
+        ..Default::default()
+    };
+    let sessions = SessionManager::new(config.session_ttl);
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
     assert_eq!(resp.status(), 401);
 }
 
 #[tokio::test]
 async fn sse_get_returns_event_stream() {
     let reg = ToolRegistry::empty_with_state(Arc::new(()));
-    let resp =
-        axum_mcp::http::handle_sse_get(&reg, AllowedOrigins::LocalhostOnly, Auth::None).await;
+    let config = McpLayerConfig::default();
+    let sessions = SessionManager::new(config.session_ttl);
+    let sid = open_session(&reg, &config, &sessions).await;
+
+    let resp = axum_mcp::http::handle_sse_get(sse_get(&sid, None), &config, &sessions).await;
     assert_eq!(resp.status(), 200);
     let ct = resp
         .headers()
@@ -63,4 +71,71 @@ async fn sse_get_returns_event_stream() {
         .to_str()
         .unwrap();
     assert!(ct.starts_with("text/event-stream"));
+
+    let mut body = resp.into_body().into_data_stream();
+    assert_eq!(next_event(&mut body).await.unwrap(), "retry:3000\n\n");
+
+    sessions.get(&sid).unwrap().notify("notifications/message", Some(serde_json::json!({"level": "info", "data": "hi"})));
+    let event = next_event(&mut body).await.unwrap();
+    assert!(event.contains("id: 1\n"), "{event}");
+    assert!(event.contains("\"method\":\"notifications/message\""), "{event}");
+
+    // Ending the session ends the stream.
+    sessions.remove(&sid);
+    assert!(next_event(&mut body).await.is_none());
+}
+
+#[tokio::test]
+async fn sse_get_requires_known_session() {
+    let reg = ToolRegistry::empty_with_state(Arc::new(()));
+    let config = McpLayerConfig::default();
+    let sessions = SessionManager::new(config.session_ttl);
+    let resp = axum_mcp::http::handle_sse_get(sse_get("nope", None), &config, &sessions).await;
+    assert_eq!(resp.status(), 404);
+
+    let sid = open_session(&reg, &config, &sessions).await;
+    let mut req = sse_get(&sid, None);
+    req.headers_mut().insert("Origin", HeaderValue::from_static("http://evil.example.com"));
+    let resp = axum_mcp::http::handle_sse_get(req, &config, &sessions).await;
+    assert_eq!(resp.status(), 403);
+}
+
+#[tokio::test]
+async fn get_is_not_allowed_without_sse() {
+    let reg = ToolRegistry::empty_with_state(Arc::new(()));
+    let config = McpLayerConfig { enable_sse: false, ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+    let sid = open_session(&reg, &config, &sessions).await;
+    let resp = axum_mcp::http::handle_sse_get(sse_get(&sid, None), &config, &sessions).await;
+    assert_eq!(resp.status(), 405);
+
+    // The layer answers for the MCP path instead of handing GET to the app.
+    let inner = tower::service_fn(|_: Request<Body>| async {
+        Ok::<_, std::convert::Infallible>(axum::http::Response::new(Body::empty()))
+    });
+    let service = McpLayer::new(reg, config).layer(inner);
+    let resp = service.oneshot(sse_get(&sid, None)).await.unwrap();
+    assert_eq!(resp.status(), 405);
+}
+
+#[tokio::test]
+async fn sse_reconnect_replays_missed_events() {
+    let reg = ToolRegistry::empty_with_state(Arc::new(()));
+    let config = McpLayerConfig { sse_retry: None, ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+    let sid = open_session(&reg, &config, &sessions).await;
+    let session = sessions.get(&sid).unwrap();
+    for n in 1..=3 {
+        session.notify("notifications/progress", Some(serde_json::json!({"n": n})));
+    }
+
+    let resp = axum_mcp::http::handle_sse_get(sse_get(&sid, Some("1")), &config, &sessions).await;
+    let mut body = resp.into_body().into_data_stream();
+    let second = next_event(&mut body).await.unwrap();
+    assert!(second.starts_with("id: 2\n") && second.contains("\"n\":2"), "{second}");
+    let third = next_event(&mut body).await.unwrap();
+    assert!(third.starts_with("id: 3\n"), "{third}");
+
+    session.notify("notifications/progress", Some(serde_json::json!({"n": 4})));
+    assert!(next_event(&mut body).await.unwrap().starts_with("id: 4\n"));
 }
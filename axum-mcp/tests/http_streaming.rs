@@ -0,0 +1,160 @@
+#![cfg(feature = "http")]
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::{HeaderValue, Request};
+use axum_mcp::session::SessionManager;
+use axum_mcp::tool::{CallToolResult, ToolCtx, ToolDescriptor, ToolError, ToolHandler};
+use axum_mcp::{McpLayerConfig, ToolRegistry};
+use serde_json::{Value, json};
+
+struct Chatty;
+#[async_trait]
+impl ToolHandler for Chatty {
+    async fn call(&self, ctx: &ToolCtx, _args: Value) -> Result<CallToolResult, ToolError> {
+        ctx.notify("notifications/message", Some(json!({"level":"info","data":"one"})));
+        ctx.notify("notifications/message", Some(json!({"level":"info","data":"two"})));
+        Ok(json!({"done": true}).into())
+    }
+}
+
+async fn registry() -> Arc<ToolRegistry> {
+    let reg = ToolRegistry::empty_with_state(Arc::new(()));
+    reg.insert(ToolDescriptor {
+        name: "chatty",
+        description: None,
+        input_schema: schemars::schema_for!(Value),
+        output_schema: schemars::schema_for!(Value),
+        handler: Arc::new(Chatty),
+        structured: true,
+        title: None,
+        annotations: Default::default(),
+    })
+    .await;
+    reg
+}
+
+fn post(accept: &'static str, body: Value) -> Request<Body> {
+    Request::post("/mcp")
+        .header("Accept", HeaderValue::from_static(accept))
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn call() -> Value {
+    json!({"jsonrpc":"2.0","id":7,"method":"tools/call","params":{"name":"chatty"}})
+}
+
+async fn body_text(resp: axum::response::Response) -> String {
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+fn sse_data(text: &str) -> Vec<Value> {
+    text.lines()
+        .filter_map(|l| l.strip_prefix("data: "))
+        .map(|d| serde_json::from_str(d).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn tools_call_streams_notifications_then_result() {
+    let reg = registry().await;
+    let config = McpLayerConfig { stateful: false, ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+
+    let req = post("application/json, text/event-stream", call());
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+
+    // The body ends on its own once the response has been sent.
+    let events = sse_data(&body_text(resp).await);
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0]["method"], "notifications/message");
+    assert_eq!(events[0]["params"]["data"], "one");
+    assert_eq!(events[1]["params"]["data"], "two");
+    assert_eq!(events[2]["id"], 7);
+    assert_eq!(events[2]["result"]["structuredContent"]["done"], true);
+}
+
+#[tokio::test]
+async fn json_only_clients_get_a_single_body() {
+    let reg = registry().await;
+    let config = McpLayerConfig { stateful: false, ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+
+    for accept in ["application/json", "application/json, text/event-stream;q=0"] {
+        let resp = axum_mcp::http::handle_post(post(accept, call()), &reg, &config, &sessions).await;
+        assert_eq!(resp.headers()["content-type"], "application/json");
+        let body: Value = serde_json::from_str(&body_text(resp).await).unwrap();
+        assert_eq!(body["result"]["structuredContent"]["done"], true);
+    }
+
+    let off = McpLayerConfig { stream_responses: false, ..config };
+    let req = post("text/event-stream", call());
+    let resp = axum_mcp::http::handle_post(req, &reg, &off, &sessions).await;
+    assert_eq!(resp.headers()["content-type"], "application/json");
+}
+
+#[tokio::test]
+async fn session_notifications_go_to_the_outbox_without_a_stream() {
+    let reg = registry().await;
+    let config = McpLayerConfig::default();
+    let sessions = SessionManager::new(config.session_ttl);
+    let init = json!({"jsonrpc":"2.0","id":0,"method":"initialize","params":{
+        "protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"t","version":"0"}}});
+    let resp = axum_mcp::http::handle_post(post("application/json", init), &reg, &config, &sessions).await;
+    let sid = resp.headers()["Mcp-Session-Id"].to_str().unwrap().to_string();
+
+    let mut req = post("application/json", call());
+    req.headers_mut().insert("Mcp-Session-Id", HeaderValue::from_str(&sid).unwrap());
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
+    assert_eq!(resp.headers()["content-type"], "application/json");
+
+    let (queued, _) = sessions.get(&sid).unwrap().outbox().subscribe(None);
+    assert_eq!(queued.len(), 2);
+}
+
+struct Steps;
+#[async_trait]
+impl ToolHandler for Steps {
+    async fn call(&self, ctx: &ToolCtx, _args: Value) -> Result<CallToolResult, ToolError> {
+        ctx.progress(1.0, Some(2.0), Some("half way"));
+        ctx.progress(2.0, Some(2.0), None);
+        Ok(json!({}).into())
+    }
+}
+
+#[tokio::test]
+async fn progress_uses_the_request_token() {
+    let reg = registry().await;
+    reg.insert(ToolDescriptor {
+        name: "steps",
+        description: None,
+        input_schema: schemars::schema_for!(Value),
+        output_schema: schemars::schema_for!(Value),
+        handler: Arc::new(Steps),
+        structured: true,
+        title: None,
+        annotations: Default::default(),
+    })
+    .await;
+    let config = McpLayerConfig { stateful: false, ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+
+    let with_token = json!({"jsonrpc":"2.0","id":1,"method":"tools/call",
+        "params":{"name":"steps","_meta":{"progressToken":42}}});
+    let resp = axum_mcp::http::handle_post(post("text/event-stream", with_token), &reg, &config, &sessions).await;
+    let events = sse_data(&body_text(resp).await);
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0]["method"], "notifications/progress");
+    assert_eq!(events[0]["params"], json!({"progressToken":42,"progress":1.0,"total":2.0,"message":"half way"}));
+    assert!(events[1]["params"].get("message").is_none());
+
+    // No token: the tool's progress reports go nowhere.
+    let without = json!({"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"steps"}});
+    let resp = axum_mcp::http::handle_post(post("text/event-stream", without), &reg, &config, &sessions).await;
+    assert_eq!(sse_data(&body_text(resp).await).len(), 1);
+}
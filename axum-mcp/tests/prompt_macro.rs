@@ -0,0 +1,89 @@
+#![cfg(feature = "http")]
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::Request;
+use axum_mcp::content::ContentBlock;
+use axum_mcp::prompt::PromptMessage;
+use axum_mcp::session::SessionManager;
+use axum_mcp::tool::ToolCtx;
+use axum_mcp::{McpLayerConfig, PromptRegistry, ToolRegistry};
+use axum_mcp_macros::mcp_prompt;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Clone)]
+struct Team {
+    name: &'static str,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ReviewArgs {
+    /// The code to review.
+    code: String,
+    /// What to pay attention to.
+    focus: Option<String>,
+}
+
+#[mcp_prompt(name = "review", desc = "Review a snippet", state = "Team")]
+async fn review(State(team): State<Team>, args: ReviewArgs, ctx: &ToolCtx) -> Vec<PromptMessage> {
+    let _ = ctx.progress_token();
+    let focus = args.focus.unwrap_or_else(|| "anything".into());
+    vec![
+        PromptMessage::user(format!("{} asks you to review, focusing on {focus}:", team.name)),
+        PromptMessage::user(ContentBlock::text(args.code)),
+    ]
+}
+
+#[mcp_prompt]
+async fn greet() -> Result<String, std::io::Error> {
+    Ok("Say hello".into())
+}
+
+async fn rpc(method: &str, params: Value) -> Value {
+    let prompts = PromptRegistry::gather_with_state(Arc::new(Team { name: "core" }));
+    let config = McpLayerConfig { stateful: false, prompts: Some(prompts), ..Default::default() };
+    let reg = ToolRegistry::empty_with_state(Arc::new(()));
+    let sessions = SessionManager::new(config.session_ttl);
+    let body = json!({"jsonrpc":"2.0","id":1,"method":method,"params":params});
+    let req = Request::post("/mcp").body(Body::from(body.to_string())).unwrap();
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn prompts_list_with_arguments_from_the_schema() {
+    let list = rpc("prompts/list", json!({})).await;
+    assert_eq!(
+        list["result"]["prompts"],
+        json!([
+            {"name":"greet"},
+            {"name":"review","description":"Review a snippet","arguments":[
+                {"name":"code","description":"The code to review.","required":true},
+                {"name":"focus","description":"What to pay attention to.","required":false}
+            ]}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn prompts_get_renders_messages() {
+    let got = rpc("prompts/get", json!({"name":"review","arguments":{"code":"fn f() {}"}})).await;
+    assert_eq!(got["result"]["description"], "Review a snippet");
+    assert_eq!(
+        got["result"]["messages"],
+        json!([
+            {"role":"user","content":{"type":"text","text":"core asks you to review, focusing on anything:"}},
+            {"role":"user","content":{"type":"text","text":"fn f() {}"}}
+        ])
+    );
+    let greet = rpc("prompts/get", json!({"name":"greet"})).await;
+    assert_eq!(greet["result"]["messages"][0]["content"]["text"], "Say hello");
+
+    for bad in [json!({"name":"review"}), json!({"name":"nope"})] {
+        assert_eq!(rpc("prompts/get", bad).await["error"]["code"], -32602);
+    }
+}
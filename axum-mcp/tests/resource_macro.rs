@@ -0,0 +1,89 @@
+#![cfg(feature = "http")]
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::{Path, State};
+use axum::http::{Request, Response};
+use axum_mcp::{McpLayer, McpLayerConfig, ResourceRegistry, ToolRegistry};
+use axum_mcp::resource::{ResourceBody, ResourceError};
+use axum_mcp::tool::ToolCtx;
+use axum_mcp_macros::mcp_resource;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tower::{Layer, ServiceExt};
+
+#[derive(Clone)]
+struct Files {
+    root: &'static str,
+}
+
+#[mcp_resource(uri = "file:///files/{id}", desc = "A stored file", mime = "text/plain", state = "Files")]
+async fn get_file(State(files): State<Files>, Path(id): Path<u32>) -> Result<String, String> {
+    match id {
+        0 => Err("file 0 is corrupt".into()),
+        id => Ok(format!("{}/{id}", files.root)),
+    }
+}
+
+#[derive(Deserialize)]
+struct PostPath {
+    user: String,
+    post: u32,
+}
+
+#[mcp_resource(uri = "blog://{user}/posts/{post}", name = "post")]
+async fn get_post(Path(p): Path<PostPath>, ctx: &ToolCtx) -> axum::Json<serde_json::Value> {
+    axum::Json(serde_json::json!({"user": p.user, "post": p.post, "cancelled": ctx.is_cancelled()}))
+}
+
+#[mcp_resource(uri = "config://app")]
+async fn app_config() -> &'static str {
+    "debug = false"
+}
+
+fn text(body: &ResourceBody) -> &str {
+    match body {
+        ResourceBody::Text(t) => t,
+        ResourceBody::Blob(_) => panic!("expected text"),
+    }
+}
+
+#[tokio::test]
+async fn macro_resources_are_gathered_and_read() {
+    let reg = ResourceRegistry::gather_with_state(Arc::new(Files { root: "/srv" }));
+    let templates = reg.templates_page(None, 0).await.unwrap().items;
+    let names: Vec<_> = templates.iter().map(|d| (d.uri.as_str(), d.name.as_str())).collect();
+    assert_eq!(names, [("blog://{user}/posts/{post}", "post"), ("file:///files/{id}", "get_file")]);
+    assert_eq!(templates[1].description.as_deref(), Some("A stored file"));
+
+    let file = reg.read("file:///files/7").await.unwrap();
+    assert_eq!(text(&file[0].body), "/srv/7");
+    assert_eq!(file[0].mime_type.as_deref(), Some("text/plain"));
+
+    let post = reg.read("blog://ann/posts/3").await.unwrap();
+    assert_eq!(post[0].mime_type.as_deref(), Some("application/json"));
+    assert_eq!(text(&post[0].body), r#"{"cancelled":false,"post":3,"user":"ann"}"#);
+
+    assert_eq!(text(&reg.read("config://app").await.unwrap()[0].body), "debug = false");
+}
+
+#[tokio::test]
+async fn bad_path_values_and_handler_errors() {
+    let reg = ResourceRegistry::gather_with_state(Arc::new(Files { root: "/srv" }));
+    assert!(matches!(reg.read("file:///files/abc").await, Err(ResourceError::NotFound(_))));
+    assert!(matches!(reg.read("file:///files/0").await, Err(ResourceError::Internal(m)) if m == "file 0 is corrupt"));
+}
+
+#[tokio::test]
+async fn mcp_layer_serves_macro_resources_by_default() {
+    let tools = ToolRegistry::gather_with_state(Arc::new(Files { root: "/srv" }));
+    let inner = tower::service_fn(|_: Request<Body>| async { Ok::<_, std::convert::Infallible>(Response::new(Body::empty())) });
+    let service = McpLayer::new(tools, McpLayerConfig::default()).layer(inner);
+    let init = json!({"jsonrpc":"2.0","id":0,"method":"initialize","params":{
+        "protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"t","version":"0"}}});
+    let req = Request::post("/mcp").body(Body::from(init.to_string())).unwrap();
+    let resp = service.oneshot(req).await.unwrap();
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["result"]["capabilities"]["resources"]["subscribe"], true);
+}
@@ -2,22 +2,24 @@
 use std::sync::Arc;
 use axum::http::{Request, HeaderValue, StatusCode};
 use axum::body::Body;
-use axum_mcp::{security::{AllowedOrigins, REQUIRED_PROTOCOL_VERSION}, ToolRegistry};
+use axum_mcp::{security::{AllowedOrigins, REQUIRED_PROTOCOL_VERSION}, McpLayerConfig, ToolRegistry};
+use axum_mcp::session::SessionManager;
+
+async fn body_json(resp: axum::response::Response) -> serde_json::Value {
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
 
 #[tokio::test]
 async fn missing_version_header_fallback_allowed() {
     let reg = ToolRegistry::empty_with_state(Arc::new(()));
     let req = Request::post("/mcp")
         .header("Origin", HeaderValue::from_static("http://127.0.0.1:3000"))
-        .body(Body::from("{\"op\":\"tools/list\"}"))
+        .body(Body::from("{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"initialize\",\"params\":{\"protocolVersion\":\"2025-03-26\",\"capabilities\":{},\"clientInfo\":{\"name\":\"t\",\"version\":\"0\"}}}"))
         .unwrap();
-    let resp = axum_mcp::http::handle_post(
-        req,
-        &reg,
-        AllowedOrigins::LocalhostAll,
-        axum_mcp::security::Auth::None,
-        axum_mcp::security::VersionPolicy::AllowFallback { required: axum_mcp::security::REQUIRED_PROTOCOL_VERSION, fallback: axum_mcp::security::FALLBACK_PROTOCOL_VERSION }
-    ).await;
+    let config = McpLayerConfig { allowed_origins: AllowedOrigins::LocalhostAll, ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
     assert_eq!(resp.status(), StatusCode::OK);
 }
 
@@ -27,15 +29,11 @@ async fn wrong_origin_rejected() {
     let req = Request::post("/mcp")
         .header("MCP-Protocol-Version", REQUIRED_PROTOCOL_VERSION)
         .header("Origin", HeaderValue::from_static("http://evil.example.com"))
-        .body(Body::from("{\"op\":\"tools/list\"}"))
+        .body(Body::from("{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"tools/list\"}"))
         .unwrap();
-    let resp = axum_mcp::http::handle_post(
-        req,
-        &reg,
-        AllowedOrigins::LocalhostOnly,
-        axum_mcp::security::Auth::None,
-        axum_mcp::security::VersionPolicy::AllowFallback { required: axum_mcp::security::REQUIRED_PROTOCOL_VERSION, fallback: axum_mcp::security::FALLBACK_PROTOCOL_VERSION }
-    ).await;
+    let config = McpLayerConfig { allowed_origins: AllowedOrigins::LocalhostOnly, ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
     assert_eq!(resp.status(), StatusCode::FORBIDDEN);
 }
 
@@ -46,14 +44,35 @@ async fn unknown_tool_and_invalid_args() {
     let req = Request::post("/mcp")
         .header("MCP-Protocol-Version", REQUIRED_PROTOCOL_VERSION)
         .header("Origin", HeaderValue::from_static("http://127.0.0.1:3000"))
-        .body(Body::from("{\"op\":\"tools/call\",\"name\":\"not_exist\",\"args\":{}}"))
+        .body(Body::from("{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"tools/call\",\"params\":{\"name\":\"not_exist\",\"arguments\":{}}}"))
         .unwrap();
-    let resp = axum_mcp::http::handle_post(
-        req,
-        &reg,
-        AllowedOrigins::LocalhostAll,
-        axum_mcp::security::Auth::None,
-        axum_mcp::security::VersionPolicy::AllowFallback { required: axum_mcp::security::REQUIRED_PROTOCOL_VERSION, fallback: axum_mcp::security::FALLBACK_PROTOCOL_VERSION }
-    ).await;
-    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let config = McpLayerConfig { allowed_origins: AllowedOrigins::LocalhostAll, stateful: false, ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(body_json(resp).await["error"]["code"], -32602);
+}
+
+#[tokio::test]
+async fn jsonrpc_parse_and_method_errors() {
+    let reg = ToolRegistry::empty_with_state(Arc::new(()));
+    let config = McpLayerConfig { stateful: false, ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+    let req = Request::post("/mcp")
+        .body(Body::from("{not json"))
+        .unwrap();
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body = body_json(resp).await;
+    assert_eq!(body["error"]["code"], -32700);
+    assert!(body["id"].is_null());
+
+    let req = Request::post("/mcp")
+        .body(Body::from("{\"jsonrpc\":\"2.0\",\"id\":9,\"method\":\"nope\"}"))
+        .unwrap();
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = body_json(resp).await;
+    assert_eq!(body["id"], 9);
+    assert_eq!(body["error"]["code"], -32601);
 }
@@ -0,0 +1,138 @@
+#![cfg(feature = "stdio")]
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::State;
+use axum_mcp::ToolRegistry;
+use axum_mcp::resource::ResourceRegistry;
+use axum_mcp::stdio::{StdioConfig, serve_io_with_config};
+use axum_mcp_macros::{mcp_resource, mcp_tool};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, Lines};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+static GATE: Notify = Notify::const_new();
+
+#[derive(Deserialize, JsonSchema)]
+struct Word {
+    word: String,
+}
+
+#[mcp_tool(state = "()")]
+async fn shout(State(()): State<()>, Json(inp): Json<Word>) -> String {
+    inp.word.to_uppercase()
+}
+
+/// Finishes only once `open_gate` has run.
+#[mcp_tool(state = "()")]
+async fn wait_for_gate(State(()): State<()>, Json(inp): Json<Word>) -> String {
+    GATE.notified().await;
+    inp.word
+}
+
+#[mcp_tool(state = "()")]
+async fn open_gate(State(()): State<()>, Json(inp): Json<Word>) -> String {
+    GATE.notify_one();
+    inp.word
+}
+
+/// Readable only once `open_gate` has run.
+#[mcp_resource(uri = "gate://slow")]
+async fn slow_resource() -> &'static str {
+    GATE.notified().await;
+    "opened"
+}
+
+struct Client {
+    input: DuplexStream,
+    output: Lines<BufReader<DuplexStream>>,
+    server: JoinHandle<anyhow::Result<()>>,
+}
+
+impl Client {
+    async fn start() -> Self {
+        let (input, server_in) = tokio::io::duplex(1 << 16);
+        let (server_out, output) = tokio::io::duplex(1 << 16);
+        let registry = ToolRegistry::gather_with_state(Arc::new(()));
+        let config = StdioConfig {
+            resources: Some(ResourceRegistry::gather_with_state(Arc::new(()))),
+            ..Default::default()
+        };
+        let server = tokio::spawn(serve_io_with_config(server_in, server_out, registry, config));
+        let mut client = Self { input, output: BufReader::new(output).lines(), server };
+        client
+            .send(json!({"jsonrpc":"2.0","id":0,"method":"initialize","params":{
+                "protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"t","version":"0"}}}))
+            .await;
+        client.send(json!({"jsonrpc":"2.0","method":"notifications/initialized"})).await;
+        assert_eq!(client.next().await["id"], 0);
+        client
+    }
+
+    async fn send(&mut self, msg: Value) {
+        self.input.write_all(format!("{msg}\n").as_bytes()).await.unwrap();
+    }
+
+    async fn next(&mut self) -> Value {
+        serde_json::from_str(&self.output.next_line().await.unwrap().unwrap()).unwrap()
+    }
+}
+
+fn call(id: u64, name: &str, word: &str) -> Value {
+    json!({"jsonrpc":"2.0","id":id,"method":"tools/call","params":{"name":name,"arguments":{"word":word}}})
+}
+
+#[tokio::test]
+async fn lists_and_calls_over_a_duplex() {
+    let mut client = Client::start().await;
+    client.send(json!({"jsonrpc":"2.0","id":1,"method":"tools/list"})).await;
+    let list = client.next().await;
+    let names: Vec<_> = list["result"]["tools"].as_array().unwrap().iter().map(|t| t["name"].clone()).collect();
+    assert!(names.contains(&json!("shout")));
+
+    client.send(call(2, "shout", "hi")).await;
+    let reply = client.next().await;
+    assert_eq!(reply["id"], 2);
+    assert_eq!(reply["result"]["content"][0]["text"], "HI");
+
+    client.input.write_all(b"{not json\n").await.unwrap();
+    assert_eq!(client.next().await["error"]["code"], -32700);
+}
+
+#[tokio::test]
+async fn calls_run_concurrently() {
+    let mut client = Client::start().await;
+    client.send(call(1, "wait_for_gate", "first")).await;
+    client.send(call(2, "open_gate", "second")).await;
+    assert_eq!(client.next().await["id"], 2);
+    assert_eq!(client.next().await["id"], 1);
+}
+
+#[tokio::test]
+async fn slow_requests_do_not_hold_up_others() {
+    let mut client = Client::start().await;
+    client.send(json!({"jsonrpc":"2.0","id":1,"method":"resources/read","params":{"uri":"gate://slow"}})).await;
+    client.send(json!({"jsonrpc":"2.0","id":2,"method":"ping"})).await;
+    assert_eq!(client.next().await["id"], 2);
+
+    client.send(call(3, "open_gate", "go")).await;
+    let mut replies = [client.next().await, client.next().await];
+    replies.sort_by_key(|r| r["id"].as_u64());
+    assert_eq!(replies[0]["result"]["contents"][0]["text"], "opened");
+    assert_eq!(replies[1]["id"], 3);
+}
+
+#[tokio::test]
+async fn eof_finishes_running_calls_then_returns() {
+    let Client { mut input, mut output, server } = Client::start().await;
+    let msg = call(1, "shout", "bye");
+    input.write_all(format!("{msg}\n").as_bytes()).await.unwrap();
+    drop(input);
+    server.await.unwrap().unwrap();
+    let reply: Value = serde_json::from_str(&output.next_line().await.unwrap().unwrap()).unwrap();
+    assert_eq!(reply["result"]["content"][0]["text"], "BYE");
+    assert!(output.next_line().await.unwrap().is_none());
+}
@@ -1,13 +1,11 @@
 #![cfg(feature = "stdio")]
-use mcp_protocol_sdk::protocol::types::{JSONRPC_VERSION, JsonRpcRequest};
-use mcp_protocol_sdk::transport::stdio::StdioClientTransport;
-use mcp_protocol_sdk::transport::traits::Transport;
 use serde_json::json;
 use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
 
-#[tokio::test]
-async fn tools_list_and_call() {
+async fn demo_exe() -> PathBuf {
     let status = Command::new("cargo")
         .args(["build", "-p", "axum-mcp-demo-stdio"])
         .status()
@@ -25,32 +23,211 @@ async fn tools_list_and_call() {
         "axum-mcp-demo-stdio"
     };
     path.push(exe);
+    path
+}
 
-    let mut transport = StdioClientTransport::new(path.to_str().unwrap(), vec![])
-        .await
-        .expect("spawn stdio server");
+#[tokio::test]
+async fn tools_list_and_call() {
+    let path = demo_exe().await;
+    let mut child = Command::new(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+
+    let init = json!({"jsonrpc":"2.0","id":0,"method":"initialize","params":{
+        "protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"roundtrip","version":"0.0.0"}}});
+    let initialized = json!({"jsonrpc":"2.0","method":"notifications/initialized"});
+    let list = json!({"jsonrpc":"2.0","id":1,"method":"tools/list"});
+    let call = json!({"jsonrpc":"2.0","id":2,"method":"tools/call",
+        "params":{"name":"sum","arguments":{"a":1,"b":2}}});
+    for msg in [init, initialized, list, call] {
+        stdin.write_all(format!("{msg}\n").as_bytes()).await.unwrap();
+    }
+    drop(stdin);
+    let mut replies = Vec::new();
+    while let Some(line) = lines.next_line().await.unwrap() {
+        replies.push(serde_json::from_str::<serde_json::Value>(&line).unwrap());
+    }
+
+    let init = &replies[0]["result"];
+    assert_eq!(init["protocolVersion"], "2025-06-18");
+    assert_eq!(init["serverInfo"]["name"], "axum-mcp");
+    assert!(init["capabilities"]["tools"].is_object());
 
-    let list_req = JsonRpcRequest {
-        jsonrpc: JSONRPC_VERSION.to_string(),
-        id: json!(1),
-        method: "tools/list".into(),
-        params: None,
+    let tools = replies[1]["result"]["tools"].as_array().unwrap();
+    let by_name = |name: &str| tools.iter().find(|t| t["name"] == name).unwrap();
+    assert_eq!(by_name("sum")["description"], "Add two integers");
+    assert_eq!(by_name("sum")["inputSchema"]["type"], "object");
+    assert_eq!(by_name("sum")["outputSchema"]["type"], "object");
+    assert_eq!(by_name("sum")["annotations"]["readOnlyHint"], true);
+    // Declared `structured = false`: no output schema, text results only.
+    assert!(by_name("summarize").get("outputSchema").is_none());
+
+    let result = &replies[2]["result"];
+    assert_eq!(result["structuredContent"]["sum"], 3);
+    assert_eq!(result["content"][0], json!({"type":"text","text":"{\"sum\":3}"}));
+    assert_eq!(result["isError"], false);
+}
+
+#[tokio::test]
+async fn progress_notifications_precede_result() {
+    let path = demo_exe().await;
+    let mut child = Command::new(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut next = async || -> serde_json::Value {
+        serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap()
     };
-    let list_resp = transport.send_request(list_req).await.unwrap();
-    let tools = list_resp.result.unwrap()["tools"]
-        .as_array()
-        .unwrap()
-        .clone();
-    assert!(tools.iter().any(|t| t["name"] == "sum"));
-
-    let call_req = JsonRpcRequest {
-        jsonrpc: JSONRPC_VERSION.to_string(),
-        id: json!(2),
-        method: "tools/call".into(),
-        params: Some(json!({"name":"sum","arguments":{"a":1,"b":2}})),
+
+    let init = json!({"jsonrpc":"2.0","id":0,"method":"initialize","params":{
+        "protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"t","version":"0"}}});
+    let call = json!({"jsonrpc":"2.0","id":1,"method":"tools/call",
+        "params":{"name":"count","arguments":{"to":2},"_meta":{"progressToken":"tok"}}});
+    for msg in [init, call] {
+        stdin.write_all(format!("{msg}\n").as_bytes()).await.unwrap();
+    }
+
+    assert_eq!(next().await["id"], 0);
+    for n in 1..=2 {
+        let p = next().await;
+        assert_eq!(p["method"], "notifications/progress");
+        assert_eq!(p["params"]["progressToken"], "tok");
+        assert_eq!(p["params"]["progress"], n as f64);
+        assert_eq!(p["params"]["total"], 2.0);
+    }
+    let done = next().await;
+    assert_eq!(done["id"], 1);
+    assert_eq!(done["result"]["structuredContent"]["counted"], 2);
+}
+
+#[tokio::test]
+async fn cancelled_call_gets_no_response() {
+    let path = demo_exe().await;
+    let mut child = Command::new(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+
+    let init = json!({"jsonrpc":"2.0","id":0,"method":"initialize","params":{
+        "protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"t","version":"0"}}});
+    let call = json!({"jsonrpc":"2.0","id":1,"method":"tools/call",
+        "params":{"name":"count","arguments":{"to":100,"delay_ms":50},"_meta":{"progressToken":"tok"}}});
+    for msg in [init, call] {
+        stdin.write_all(format!("{msg}\n").as_bytes()).await.unwrap();
+    }
+    let mut seen = Vec::new();
+    // Wait until the call is demonstrably running, then cancel it.
+    while let Some(line) = lines.next_line().await.unwrap() {
+        let msg: serde_json::Value = serde_json::from_str(&line).unwrap();
+        let running = msg["method"] == "notifications/progress";
+        seen.push(msg);
+        if running {
+            break;
+        }
+    }
+    let cancel = json!({"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":1}});
+    stdin.write_all(format!("{cancel}\n").as_bytes()).await.unwrap();
+    drop(stdin);
+
+    // The server exits once the cancelled call has wound down.
+    while let Some(line) = lines.next_line().await.unwrap() {
+        seen.push(serde_json::from_str(&line).unwrap());
+    }
+    assert!(seen.iter().any(|m| m["id"] == 0));
+    assert!(!seen.iter().any(|m| m["id"] == 1));
+    let progress = seen.iter().filter(|m| m["method"] == "notifications/progress").count();
+    assert!(progress < 100);
+}
+
+#[tokio::test]
+async fn prompts_list_and_get() {
+    let path = demo_exe().await;
+    let mut child = Command::new(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+
+    let init = json!({"jsonrpc":"2.0","id":0,"method":"initialize","params":{
+        "protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"t","version":"0"}}});
+    let list = json!({"jsonrpc":"2.0","id":1,"method":"prompts/list"});
+    let get = json!({"jsonrpc":"2.0","id":2,"method":"prompts/get","params":{"name":"explain","arguments":{"n":"42"}}});
+    let missing = json!({"jsonrpc":"2.0","id":3,"method":"prompts/get","params":{"name":"explain"}});
+    for msg in [init, list, get, missing] {
+        stdin.write_all(format!("{msg}\n").as_bytes()).await.unwrap();
+    }
+    drop(stdin);
+    let mut replies = Vec::new();
+    while let Some(line) = lines.next_line().await.unwrap() {
+        replies.push(serde_json::from_str::<serde_json::Value>(&line).unwrap());
+    }
+
+    assert!(replies[0]["result"]["capabilities"]["prompts"].is_object());
+    assert_eq!(
+        replies[1]["result"]["prompts"],
+        json!([{"name":"explain","description":"Ask for an explanation of a number",
+            "arguments":[{"name":"n","description":"The integer to explain.","required":true}]}])
+    );
+    let messages = &replies[2]["result"]["messages"];
+    assert_eq!(messages[0], json!({"role":"user","content":{"type":"text","text":"What is special about 42?"}}));
+    assert_eq!(messages[1]["role"], "assistant");
+    assert_eq!(replies[3]["error"]["code"], -32602);
+}
+
+#[tokio::test]
+async fn tools_can_sample_the_client() {
+    let path = demo_exe().await;
+    let mut child = Command::new(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut next = async || -> serde_json::Value {
+        serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap()
     };
-    let call_resp = transport.send_request(call_req).await.unwrap();
-    assert_eq!(call_resp.result.unwrap()["result"]["sum"], 3);
 
-    transport.close().await.unwrap();
+    let init = json!({"jsonrpc":"2.0","id":0,"method":"initialize","params":{
+        "protocolVersion":"2025-06-18","capabilities":{"sampling":{}},"clientInfo":{"name":"t","version":"0"}}});
+    let call = json!({"jsonrpc":"2.0","id":1,"method":"tools/call",
+        "params":{"name":"summarize","arguments":{"text":"a long story"}}});
+    for msg in [init, call] {
+        stdin.write_all(format!("{msg}\n").as_bytes()).await.unwrap();
+    }
+    assert_eq!(next().await["id"], 0);
+
+    let req = next().await;
+    assert_eq!(req["method"], "sampling/createMessage");
+    assert_eq!(req["params"]["messages"][0]["content"]["text"], "a long story");
+    let answer = json!({"jsonrpc":"2.0","id":req["id"],"result":{
+        "role":"assistant","content":{"type":"text","text":"It was long."},"model":"m"}});
+    stdin.write_all(format!("{answer}\n").as_bytes()).await.unwrap();
+
+    let done = next().await;
+    assert_eq!(done["id"], 1);
+    assert!(done["result"].get("structuredContent").is_none());
+    assert_eq!(done["result"]["content"][0]["text"], "{\"summary\":\"It was long.\"}");
 }
@@ -0,0 +1,124 @@
+#![cfg(feature = "http")]
+use std::sync::Arc;
+
+use axum::Json;
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::Request;
+use axum_mcp::session::SessionManager;
+use axum_mcp::tool::ToolAnnotations;
+use axum_mcp::{McpLayerConfig, ToolRegistry};
+use axum_mcp_macros::mcp_tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Deserialize, JsonSchema)]
+struct Id {
+    id: u32,
+}
+
+#[mcp_tool(name = "show_order", title = "Show order", state = "()", read_only, open_world = false)]
+async fn show_order(State(()): State<()>, Json(inp): Json<Id>) -> String {
+    format!("order {}", inp.id)
+}
+
+#[mcp_tool(state = "()", destructive, idempotent = true)]
+async fn cancel_order(State(()): State<()>, Json(inp): Json<Id>) -> String {
+    format!("cancelled {}", inp.id)
+}
+
+#[derive(Serialize, JsonSchema)]
+struct Order {
+    id: u32,
+}
+
+#[mcp_tool(state = "()")]
+async fn json_plain(State(()): State<()>, Json(inp): Json<Id>) -> Json<Order> {
+    Json(Order { id: inp.id })
+}
+
+#[mcp_tool(state = "()", structured)]
+async fn json_structured(State(()): State<()>, Json(inp): Json<Id>) -> Json<Order> {
+    Json(Order { id: inp.id })
+}
+
+#[mcp_tool(state = "()", structured)]
+async fn json_list(State(()): State<()>, Json(inp): Json<Id>) -> Json<Vec<Order>> {
+    Json(vec![Order { id: inp.id }, Order { id: inp.id + 1 }])
+}
+
+#[mcp_tool(state = "()")]
+async fn plain(State(()): State<()>, Json(inp): Json<Id>) -> String {
+    inp.id.to_string()
+}
+
+async fn tools_list() -> Vec<Value> {
+    let reg = ToolRegistry::gather_with_state(Arc::new(()));
+    let config = McpLayerConfig { stateful: false, ..Default::default() };
+    let sessions = SessionManager::new(config.session_ttl);
+    let body = json!({"jsonrpc":"2.0","id":1,"method":"tools/list"});
+    let req = Request::post("/mcp").body(Body::from(body.to_string())).unwrap();
+    let resp = axum_mcp::http::handle_post(req, &reg, &config, &sessions).await;
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&bytes).unwrap();
+    body["result"]["tools"].as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn annotations_reach_the_descriptor() {
+    let reg = ToolRegistry::gather_with_state(Arc::new(()));
+    let d = reg.get("cancel_order").await.unwrap();
+    assert_eq!(d.title, None);
+    assert_eq!(
+        d.annotations,
+        ToolAnnotations { destructive_hint: Some(true), idempotent_hint: Some(true), ..Default::default() }
+    );
+}
+
+#[tokio::test]
+async fn tools_list_carries_titles_and_hints() {
+    let tools = tools_list().await;
+    let by_name = |name: &str| tools.iter().find(|t| t["name"] == name).unwrap().clone();
+
+    let show = by_name("show_order");
+    assert_eq!(show["title"], "Show order");
+    assert_eq!(show["annotations"], json!({"readOnlyHint": true, "openWorldHint": false}));
+
+    let plain = by_name("plain");
+    assert!(plain.get("title").is_none());
+    assert!(plain.get("annotations").is_none());
+    assert!(plain.get("outputSchema").is_none());
+    assert_eq!(plain["inputSchema"]["required"], json!(["id"]));
+}
+
+#[tokio::test]
+async fn structured_output_is_opt_in() {
+    let tools = tools_list().await;
+    let by_name = |name: &str| tools.iter().find(|t| t["name"] == name).unwrap().clone();
+    assert!(by_name("json_plain").get("outputSchema").is_none());
+    assert_eq!(by_name("json_structured")["outputSchema"]["required"], json!(["id"]));
+
+    let reg = ToolRegistry::gather_with_state(Arc::new(()));
+    let plain = reg.call("json_plain", json!({"id": 4})).await.unwrap();
+    assert_eq!(plain.structured_content, None);
+    let structured = reg.call("json_structured", json!({"id": 4})).await.unwrap();
+    assert_eq!(structured.structured_content, Some(json!({"id": 4})));
+}
+
+#[tokio::test]
+async fn non_object_output_is_wrapped_under_result() {
+    let tools = tools_list().await;
+    let list = tools.iter().find(|t| t["name"] == "json_list").unwrap();
+    let schema = &list["outputSchema"];
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["required"], json!(["result"]));
+    assert_eq!(schema["properties"]["result"]["type"], "array");
+    // `$ref`s into the hoisted definitions still resolve from the root.
+    let item_ref = schema["properties"]["result"]["items"]["$ref"].as_str().unwrap();
+    assert!(schema.pointer(item_ref.trim_start_matches('#')).is_some());
+
+    let reg = ToolRegistry::gather_with_state(Arc::new(()));
+    let out = reg.call("json_list", json!({"id": 4})).await.unwrap();
+    assert_eq!(out.structured_content, Some(json!({"result": [{"id": 4}, {"id": 5}]})));
+}
@@ -3,7 +3,9 @@
 use std::sync::Arc;
 
 use axum_mcp::ToolRegistry;
-use axum_mcp_macros::mcp_tool;
+use axum_mcp::prompt::PromptMessage;
+use axum_mcp::sampling::{CreateMessageRequest, SamplingMessage};
+use axum_mcp_macros::{mcp_prompt, mcp_tool};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -21,7 +23,7 @@ struct SumOut {
     sum: i64,
 }
 
-#[mcp_tool(name = "sum", desc = "Add two integers", state = "AppState")]
+#[mcp_tool(name = "sum", desc = "Add two integers", state = "AppState", structured, read_only, open_world = false)]
 async fn sum(
     axum::extract::State(state): axum::extract::State<AppState>,
     axum::Json(inp): axum::Json<SumIn>,
@@ -30,6 +32,69 @@ async fn sum(
     axum::Json(SumOut { sum: inp.a + inp.b })
 }
 
+#[derive(Deserialize, JsonSchema)]
+struct CountIn {
+    to: u32,
+    /// Pause between steps, in milliseconds.
+    #[serde(default)]
+    delay_ms: u64,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct CountOut {
+    counted: u32,
+}
+
+#[mcp_tool(name = "count", desc = "Count up, reporting progress", state = "AppState", structured)]
+async fn count(
+    axum::extract::State(_state): axum::extract::State<AppState>,
+    ctx: &axum_mcp::tool::ToolCtx,
+    axum::Json(inp): axum::Json<CountIn>,
+) -> axum::Json<CountOut> {
+    for n in 1..=inp.to {
+        tokio::time::sleep(std::time::Duration::from_millis(inp.delay_ms)).await;
+        ctx.progress(n.into(), Some(inp.to.into()), None);
+    }
+    axum::Json(CountOut { counted: inp.to })
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SummarizeIn {
+    text: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct SummarizeOut {
+    summary: String,
+}
+
+#[mcp_tool(name = "summarize", desc = "Summarize text with the client's model", state = "AppState", structured = false)]
+async fn summarize(
+    axum::extract::State(_state): axum::extract::State<AppState>,
+    ctx: &axum_mcp::tool::ToolCtx,
+    axum::Json(inp): axum::Json<SummarizeIn>,
+) -> Result<axum::Json<SummarizeOut>, axum_mcp::peer::PeerError> {
+    let req = CreateMessageRequest::new(vec![SamplingMessage::user(inp.text)], 100)
+        .with_system_prompt("Summarize in one sentence.");
+    let reply = ctx.sample(req).await?;
+    let summary = reply.text().unwrap_or_default().to_string();
+    Ok(axum::Json(SummarizeOut { summary }))
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ExplainIn {
+    /// The integer to explain.
+    n: String,
+}
+
+#[mcp_prompt(name = "explain", desc = "Ask for an explanation of a number")]
+async fn explain(args: ExplainIn) -> Vec<PromptMessage> {
+    vec![
+        PromptMessage::user(format!("What is special about {}?", args.n)),
+        PromptMessage::assistant("Let me check with the sum tool."),
+    ]
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let state = Arc::new(AppState);
@@ -38,8 +103,11 @@ async fn main() -> anyhow::Result<()> {
         "{}",
         r#"STDIO demo ready.
 Type JSON-RPC lines like:
+{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"cli","version":"0"}}}
 {"jsonrpc":"2.0","id":1,"method":"tools/list"}
-{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"sum","arguments":{"a":1,"b":2}}}"#
+{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"sum","arguments":{"a":1,"b":2}}}
+{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"count","arguments":{"to":3},"_meta":{"progressToken":"p"}}}
+{"jsonrpc":"2.0","id":4,"method":"prompts/get","params":{"name":"explain","arguments":{"n":"42"}}}"#
     );
     axum_mcp::stdio::run_stdio(registry, state).await
 }
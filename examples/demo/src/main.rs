@@ -1,8 +1,8 @@
 use std::sync::Arc;
 
-use axum::{Router, routing::post};
+use axum::{Router, routing::{get, post}};
 use axum_mcp::{McpLayer, McpLayerConfig, ToolRegistry};
-use axum_mcp_macros::mcp_tool;
+use axum_mcp_macros::{mcp_resource, mcp_tool};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -15,12 +15,17 @@ struct SumIn { a: i64, b: i64 }
 #[derive(Serialize, JsonSchema)]
 struct SumOut { sum: i64 }
 
-#[mcp_tool(name="sum", desc="Add two integers", state = "AppState")]
+#[mcp_tool(name="sum", title="Sum", desc="Add two integers", state = "AppState", structured, read_only, open_world = false)]
 async fn sum(axum::extract::State(state): axum::extract::State<AppState>, axum::Json(inp): axum::Json<SumIn>) -> axum::Json<SumOut> {
     let _ = state; // unused for demo
     axum::Json(SumOut { sum: inp.a + inp.b })
 }
 
+#[mcp_resource(uri = "file:///files/{id}", desc = "A demo file", mime = "text/plain", state = "AppState")]
+async fn get_file(axum::extract::State(_state): axum::extract::State<AppState>, axum::extract::Path(id): axum::extract::Path<u32>) -> String {
+    format!("contents of file {id}\n")
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let state = Arc::new(AppState);
@@ -28,6 +33,7 @@ async fn main() -> anyhow::Result<()> {
 
     let app = Router::new()
         .route("/sum", post(sum))
+        .route("/files/{id}", get(get_file))
         .layer(McpLayer::new(registry, McpLayerConfig {
             path: "/mcp",
             require_version: true,
@@ -35,6 +41,7 @@ async fn main() -> anyhow::Result<()> {
             enable_sse: true,
             auth: axum_mcp::security::Auth::None,
             version_policy: axum_mcp::security::VersionPolicy::AllowFallback { required: axum_mcp::security::REQUIRED_PROTOCOL_VERSION, fallback: axum_mcp::security::FALLBACK_PROTOCOL_VERSION },
+            ..Default::default()
         }))
         .with_state((*state).clone());
 